@@ -0,0 +1,9 @@
+CREATE TABLE sessions (
+    id INTEGER PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    session_key VARCHAR NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_seen TIMESTAMP NOT NULL DEFAULT NOW(),
+    user_agent VARCHAR,
+    CONSTRAINT fk_users FOREIGN KEY(user_id) REFERENCES users(id)
+);
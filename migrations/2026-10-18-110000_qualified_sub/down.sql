@@ -0,0 +1,3 @@
+UPDATE users
+SET sub = substring(sub FROM length('google:') + 1)
+WHERE sub LIKE 'google:%';
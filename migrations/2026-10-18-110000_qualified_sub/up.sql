@@ -0,0 +1,4 @@
+-- Every existing account signed in through Google, whose subjects never contain a colon
+UPDATE users
+SET sub = 'google:' || sub
+WHERE sub NOT LIKE '%:%';
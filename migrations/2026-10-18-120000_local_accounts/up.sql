@@ -0,0 +1,2 @@
+ALTER TABLE users
+    ADD COLUMN password_hash VARCHAR;
\ No newline at end of file
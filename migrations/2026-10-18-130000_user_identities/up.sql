@@ -0,0 +1,14 @@
+CREATE TABLE user_identities (
+    id INTEGER PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id INTEGER NOT NULL,
+    provider VARCHAR NOT NULL,
+    subject VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE(provider, subject),
+    CONSTRAINT fk_users FOREIGN KEY(user_id) REFERENCES users(id)
+);
+INSERT INTO user_identities(user_id, provider, subject)
+SELECT id,
+    split_part(sub, ':', 1),
+    substring(sub FROM position(':' IN sub) + 1)
+FROM users;
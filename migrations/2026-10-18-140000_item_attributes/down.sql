@@ -0,0 +1,9 @@
+ALTER TABLE items
+    DROP COLUMN brand,
+    DROP COLUMN size,
+    DROP COLUMN color,
+    DROP COLUMN material,
+    DROP COLUMN purchase_date,
+    DROP COLUMN purchase_price_cents,
+    DROP COLUMN purchase_currency,
+    DROP COLUMN notes;
\ No newline at end of file
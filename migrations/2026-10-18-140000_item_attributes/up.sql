@@ -0,0 +1,9 @@
+ALTER TABLE items
+    ADD COLUMN brand VARCHAR,
+    ADD COLUMN size VARCHAR,
+    ADD COLUMN color VARCHAR,
+    ADD COLUMN material VARCHAR,
+    ADD COLUMN purchase_date DATE,
+    ADD COLUMN purchase_price_cents INTEGER CHECK (purchase_price_cents >= 0),
+    ADD COLUMN purchase_currency VARCHAR(3),
+    ADD COLUMN notes TEXT;
@@ -0,0 +1,5 @@
+ALTER TABLE uses
+    DROP COLUMN outfit_wear_id;
+DROP TABLE outfit_wears;
+DROP TABLE outfit_items;
+DROP TABLE outfits;
\ No newline at end of file
@@ -0,0 +1,23 @@
+CREATE TABLE outfits (
+    id INTEGER PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id INTEGER NOT NULL,
+    outfit_name VARCHAR NOT NULL,
+    CONSTRAINT fk_users FOREIGN KEY(user_id) REFERENCES users(id)
+);
+CREATE TABLE outfit_items (
+    id INTEGER PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    outfit_id INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    UNIQUE(outfit_id, item_id),
+    CONSTRAINT fk_outfits FOREIGN KEY(outfit_id) REFERENCES outfits(id),
+    CONSTRAINT fk_items FOREIGN KEY(item_id) REFERENCES items(id)
+);
+CREATE TABLE outfit_wears (
+    id INTEGER PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    outfit_id INTEGER NOT NULL,
+    date DATE NOT NULL DEFAULT CURRENT_DATE,
+    CONSTRAINT fk_outfits FOREIGN KEY(outfit_id) REFERENCES outfits(id)
+);
+ALTER TABLE uses
+    ADD COLUMN outfit_wear_id INTEGER,
+    ADD CONSTRAINT fk_outfit_wears FOREIGN KEY(outfit_wear_id) REFERENCES outfit_wears(id);
@@ -0,0 +1,4 @@
+ALTER TABLE item_inventory
+    DROP COLUMN reason,
+    DROP COLUMN note,
+    DROP COLUMN value_cents;
\ No newline at end of file
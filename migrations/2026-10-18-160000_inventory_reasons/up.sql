@@ -0,0 +1,10 @@
+ALTER TABLE item_inventory
+    ADD COLUMN reason VARCHAR NOT NULL DEFAULT 'correction',
+    ADD COLUMN note TEXT,
+    ADD COLUMN value_cents INTEGER CHECK (value_cents >= 0),
+    ADD CONSTRAINT inventory_reason CHECK (
+        reason IN ('purchased', 'gifted', 'discarded', 'lost', 'sold', 'donated', 'correction')
+    );
+UPDATE item_inventory SET reason = 'purchased' WHERE movement > 0;
+ALTER TABLE item_inventory
+    ALTER COLUMN reason DROP DEFAULT;
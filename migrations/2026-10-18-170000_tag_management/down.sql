@@ -0,0 +1,4 @@
+ALTER TABLE tags
+    DROP CONSTRAINT unique_tag_name,
+    DROP COLUMN icon,
+    DROP COLUMN color;
\ No newline at end of file
@@ -0,0 +1,21 @@
+-- Duplicate names of the same user collapse into the oldest tag
+CREATE TEMPORARY TABLE tag_duplicates AS
+SELECT dup.id AS dup_id, MIN(keep.id) AS keep_id
+FROM tags dup
+JOIN tags keep ON keep.user_id = dup.user_id AND keep.tag_name = dup.tag_name AND keep.id < dup.id
+GROUP BY dup.id;
+DELETE FROM item_tags dup USING tag_duplicates d, item_tags keep
+WHERE dup.tag_id = d.dup_id AND keep.tag_id = d.keep_id AND keep.item_id = dup.item_id;
+DELETE FROM item_tags a USING item_tags b, tag_duplicates da, tag_duplicates db
+WHERE a.tag_id = da.dup_id AND b.tag_id = db.dup_id AND da.keep_id = db.keep_id
+    AND a.item_id = b.item_id AND a.id > b.id;
+UPDATE item_tags SET tag_id = d.keep_id
+FROM tag_duplicates d
+WHERE item_tags.tag_id = d.dup_id;
+DELETE FROM tags USING tag_duplicates d
+WHERE tags.id = d.dup_id;
+DROP TABLE tag_duplicates;
+ALTER TABLE tags
+    ADD COLUMN color VARCHAR(7),
+    ADD COLUMN icon VARCHAR,
+    ADD CONSTRAINT unique_tag_name UNIQUE(user_id, tag_name);
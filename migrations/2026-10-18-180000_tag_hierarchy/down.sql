@@ -0,0 +1,2 @@
+ALTER TABLE tags
+    DROP COLUMN parent_id;
\ No newline at end of file
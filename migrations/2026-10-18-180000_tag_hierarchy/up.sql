@@ -0,0 +1,4 @@
+ALTER TABLE tags
+    ADD COLUMN parent_id INTEGER,
+    ADD CONSTRAINT fk_parent_tags FOREIGN KEY(parent_id) REFERENCES tags(id),
+    ADD CONSTRAINT tag_not_own_parent CHECK (parent_id <> id);
@@ -0,0 +1 @@
+DROP TABLE smart_tags;
\ No newline at end of file
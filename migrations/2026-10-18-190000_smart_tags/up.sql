@@ -0,0 +1,8 @@
+CREATE TABLE smart_tags (
+    id INTEGER PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id INTEGER NOT NULL,
+    smart_tag_name VARCHAR NOT NULL,
+    expression TEXT NOT NULL,
+    UNIQUE(user_id, smart_tag_name),
+    CONSTRAINT fk_users FOREIGN KEY(user_id) REFERENCES users(id)
+);
@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use crate::api::analytics::queries::{load_items, load_wears, DateRange};
+use crate::api::item_management::attributes::parse_date;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use chrono::{Duration, Local};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+const WINDOWS: [i64; 3] = [30, 90, 365];
+
+#[derive(Serialize)]
+pub struct Coverage {
+    pub days: i64,
+    pub items_used: usize,
+    pub items_total: usize,
+    /// `items_used / items_total`, between 0 and 1.
+    pub share: f64,
+}
+
+/// Share of the wardrobe used within the last 30, 90 and 365 days up to `to`, today by default.
+#[get("/analytics/coverage?<to>")]
+pub(crate) async fn coverage(
+    user: UserLoggedIn,
+    to: Option<String>,
+    conn: DbConn,
+) -> Result<Json<Vec<Coverage>>, ErrorResponse> {
+    let to = match to {
+        Some(to) => parse_date(&to)?,
+        None => Local::now().date_naive(),
+    };
+    let range = DateRange {
+        from: Some(to - Duration::days(WINDOWS[WINDOWS.len() - 1] - 1)),
+        to: Some(to),
+    };
+
+    let uid = user.0.id;
+    let (item_list, wears) = conn
+        .run(move |c| {
+            Ok::<_, diesel::result::Error>((load_items(c, uid)?, load_wears(c, uid, range)?))
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load statistics".to_string())
+        })?;
+
+    let items_total = item_list.len();
+    Ok(Json(
+        WINDOWS
+            .iter()
+            .map(|days| {
+                let start = to - Duration::days(days - 1);
+                let items_used = wears
+                    .iter()
+                    .filter(|(_, date)| *date >= start)
+                    .map(|(iid, _)| iid)
+                    .collect::<HashSet<_>>()
+                    .len();
+
+                Coverage {
+                    days: *days,
+                    items_used,
+                    items_total,
+                    share: if items_total == 0 {
+                        0.0
+                    } else {
+                        items_used as f64 / items_total as f64
+                    },
+                }
+            })
+            .collect(),
+    ))
+}
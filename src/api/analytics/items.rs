@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use crate::api::analytics::queries::{load_items, load_wears, DateRange};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use chrono::NaiveDate;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+const DEFAULT_LIMIT: usize = 10;
+
+#[derive(Serialize, Clone)]
+pub struct ItemWears {
+    pub item_id: i32,
+    pub item_name: String,
+    pub wears: i64,
+    pub last_worn: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+pub struct ItemRanking {
+    pub most_worn: Vec<ItemWears>,
+    /// Includes items that weren't worn at all.
+    pub least_worn: Vec<ItemWears>,
+}
+
+#[get("/analytics/items?<from>&<to>&<limit>")]
+pub(crate) async fn item_ranking(
+    user: UserLoggedIn,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+    conn: DbConn,
+) -> Result<Json<ItemRanking>, ErrorResponse> {
+    let range = DateRange::parse(from, to)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    let uid = user.0.id;
+    let (item_list, wears) = conn
+        .run(move |c| {
+            Ok::<_, diesel::result::Error>((load_items(c, uid)?, load_wears(c, uid, range)?))
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load statistics".to_string())
+        })?;
+
+    let mut per_item = HashMap::<i32, (i64, NaiveDate)>::new();
+    for (iid, date) in wears {
+        let entry = per_item.entry(iid).or_insert((0, date));
+        entry.0 += 1;
+        entry.1 = entry.1.max(date);
+    }
+
+    let mut ranking = item_list
+        .into_iter()
+        .map(|(iid, name)| {
+            let worn = per_item.get(&iid);
+            ItemWears {
+                item_id: iid,
+                item_name: name,
+                wears: worn.map_or(0, |(wears, _)| *wears),
+                last_worn: worn.map(|(_, last)| *last),
+            }
+        })
+        .collect::<Vec<_>>();
+    ranking.sort_by(|a, b| {
+        b.wears
+            .cmp(&a.wears)
+            .then_with(|| a.item_name.cmp(&b.item_name))
+    });
+
+    Ok(Json(ItemRanking {
+        most_worn: ranking.iter().take(limit).cloned().collect(),
+        least_worn: ranking.iter().rev().take(limit).cloned().collect(),
+    }))
+}
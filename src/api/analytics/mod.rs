@@ -0,0 +1,8 @@
+pub(crate) mod coverage;
+pub(crate) mod items;
+pub(crate) mod monthly;
+pub(crate) mod queries;
+pub(crate) mod smart_tags;
+pub(crate) mod streaks;
+pub(crate) mod tags;
+pub(crate) mod weekdays;
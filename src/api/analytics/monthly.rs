@@ -0,0 +1,92 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::api::analytics::queries::{load_wears, DateRange};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use chrono::{Datelike, NaiveDate};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct MonthWears {
+    /// `YYYY-MM`
+    pub month: String,
+    pub wears: i64,
+    /// Distinct items worn in the month.
+    pub items: usize,
+}
+
+/// Keeps a use backdated by centuries from producing a bucket for every month since.
+const MAX_MONTHS: i32 = 1200;
+
+/// Months since year 0, so ranges of months are ranges of numbers.
+fn month_index(date: NaiveDate) -> i32 {
+    date.year() * 12 + date.month0() as i32
+}
+
+/// Wears per month, including months without any. Without explicit bounds, the range starts at
+/// the first and ends at the last wear, cut to the `MAX_MONTHS` closest to the explicit bound or,
+/// without any, the latest ones.
+#[get("/analytics/monthly?<from>&<to>")]
+pub(crate) async fn monthly_wears(
+    user: UserLoggedIn,
+    from: Option<String>,
+    to: Option<String>,
+    conn: DbConn,
+) -> Result<Json<Vec<MonthWears>>, ErrorResponse> {
+    let range = DateRange::parse(from, to)?;
+
+    let uid = user.0.id;
+    let wears = conn
+        .run(move |c| load_wears(c, uid, range))
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load statistics".to_string())
+        })?;
+
+    let first = range.from.or_else(|| wears.first().map(|(_, date)| *date));
+    let last = range.to.or_else(|| wears.last().map(|(_, date)| *date));
+    let (mut first, mut last) = match (first, last) {
+        (Some(first), Some(last)) => (month_index(first), month_index(last)),
+        _ => return Ok(Json(Vec::new())),
+    };
+    if last - first >= MAX_MONTHS {
+        match (range.from, range.to) {
+            (Some(_), Some(_)) => {
+                return Err(ErrorResponse::new(
+                    Status { code: 400 },
+                    format!("Range must not span more than {} months", MAX_MONTHS),
+                ))
+            }
+            (Some(_), None) => last = first + MAX_MONTHS - 1,
+            _ => first = last - MAX_MONTHS + 1,
+        }
+    }
+
+    let mut months = (first..=last)
+        .map(|month| (month, (0, HashSet::new())))
+        .collect::<BTreeMap<i32, (i64, HashSet<i32>)>>();
+    for (iid, date) in wears {
+        if let Some((wears, items)) = months.get_mut(&month_index(date)) {
+            *wears += 1;
+            items.insert(iid);
+        }
+    }
+
+    Ok(Json(
+        months
+            .into_iter()
+            .map(|(month, (wears, items))| MonthWears {
+                month: format!(
+                    "{:04}-{:02}",
+                    month.div_euclid(12),
+                    month.rem_euclid(12) + 1
+                ),
+                wears,
+                items: items.len(),
+            })
+            .collect(),
+    ))
+}
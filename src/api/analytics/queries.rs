@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::api::item_management::attributes::parse_date;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use rocket::http::Status;
+use serde::Serialize;
+
+/// Inclusive range of days a statistic covers, open ended where a bound is missing.
+#[derive(Clone, Copy)]
+pub(crate) struct DateRange {
+    pub(crate) from: Option<NaiveDate>,
+    pub(crate) to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub(crate) fn parse(from: Option<String>, to: Option<String>) -> Result<Self, ErrorResponse> {
+        let range = DateRange {
+            from: from.map(|from| parse_date(&from)).transpose()?,
+            to: to.map(|to| parse_date(&to)).transpose()?,
+        };
+
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            if from > to {
+                return Err(ErrorResponse::new(
+                    Status { code: 400 },
+                    "Start of range must not be after its end".to_string(),
+                ));
+            }
+        }
+
+        Ok(range)
+    }
+}
+
+/// Every use of the user's items within `range` as `(item_id, date)`, oldest first.
+pub(crate) fn load_wears(
+    c: &PgConnection,
+    uid: i32,
+    range: DateRange,
+) -> QueryResult<Vec<(i32, NaiveDate)>> {
+    use schema::items;
+    use schema::uses;
+
+    let mut query = uses::table
+        .inner_join(items::table)
+        .filter(items::user_id.eq(uid))
+        .select((uses::item_id, uses::date))
+        .into_boxed();
+    if let Some(from) = range.from {
+        query = query.filter(uses::date.ge(from));
+    }
+    if let Some(to) = range.to {
+        query = query.filter(uses::date.le(to));
+    }
+
+    query.order((uses::date, uses::id)).load(c)
+}
+
+/// The user's items as `(id, item_name)`.
+pub(crate) fn load_items(c: &PgConnection, uid: i32) -> QueryResult<Vec<(i32, String)>> {
+    use schema::items::dsl::*;
+
+    items
+        .filter(user_id.eq(uid))
+        .order(id)
+        .select((id, item_name))
+        .load(c)
+}
+
+#[derive(Serialize)]
+pub struct WearTotals {
+    pub wears: i64,
+    /// Distinct items of the group that were worn.
+    pub items_worn: usize,
+    /// All items of the group.
+    pub items: usize,
+}
+
+impl WearTotals {
+    /// Totals of a group of items, given the wears of every worn item.
+    pub(crate) fn of(group: &HashSet<i32>, item_wears: &HashMap<i32, i64>) -> Self {
+        WearTotals {
+            wears: group.iter().filter_map(|iid| item_wears.get(iid)).sum(),
+            items_worn: group
+                .iter()
+                .filter(|iid| item_wears.contains_key(iid))
+                .count(),
+            items: group.len(),
+        }
+    }
+}
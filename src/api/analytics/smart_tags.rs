@@ -0,0 +1,79 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::api::analytics::queries::{load_wears, DateRange, WearTotals};
+use crate::api::smart_tag_management::filter::{matching_items, FilterContext};
+use crate::api::smart_tag_management::models::SmartTag;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::Local;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct SmartTagWears {
+    pub smart_tag_id: i32,
+    pub smart_tag_name: String,
+    #[serde(flatten)]
+    pub totals: WearTotals,
+}
+
+/// Wears of the items each smart tag selects, most worn smart tag first. Smart tags select their
+/// items as of today, whatever the range.
+#[get("/analytics/smart_tags?<from>&<to>")]
+pub(crate) async fn smart_tag_wears(
+    user: UserLoggedIn,
+    from: Option<String>,
+    to: Option<String>,
+    conn: DbConn,
+) -> Result<Json<Vec<SmartTagWears>>, ErrorResponse> {
+    let range = DateRange::parse(from, to)?;
+
+    let uid = user.0.id;
+    let (selections, wears) = conn
+        .run(move |c| {
+            let smart_tag_list = {
+                use schema::smart_tags::dsl::*;
+                smart_tags.filter(user_id.eq(uid)).load::<SmartTag>(c)
+            }?;
+            let context = FilterContext::load(c, uid, Local::now().date_naive())?;
+            let selections = smart_tag_list
+                .into_iter()
+                .map(|smart_tag| {
+                    let selected = matching_items(c, uid, &smart_tag.expression, &context)?;
+                    Ok((smart_tag, selected.into_iter().collect::<HashSet<_>>()))
+                })
+                .collect::<QueryResult<Vec<_>>>()?;
+
+            Ok::<_, diesel::result::Error>((selections, load_wears(c, uid, range)?))
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load statistics".to_string())
+        })?;
+
+    let mut item_wears = HashMap::<i32, i64>::new();
+    for (iid, _) in wears {
+        *item_wears.entry(iid).or_default() += 1;
+    }
+
+    let mut out = selections
+        .into_iter()
+        .map(|(smart_tag, selected)| SmartTagWears {
+            smart_tag_id: smart_tag.id,
+            smart_tag_name: smart_tag.smart_tag_name,
+            totals: WearTotals::of(&selected, &item_wears),
+        })
+        .collect::<Vec<_>>();
+    out.sort_by(|a, b| {
+        b.totals
+            .wears
+            .cmp(&a.totals.wears)
+            .then_with(|| a.smart_tag_name.cmp(&b.smart_tag_name))
+    });
+
+    Ok(Json(out))
+}
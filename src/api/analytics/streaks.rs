@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use crate::api::analytics::queries::{load_items, load_wears, DateRange};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use chrono::NaiveDate;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+const DEFAULT_LIMIT: usize = 10;
+
+/// Consecutive days with at least one wear.
+#[derive(Serialize, Clone, Copy)]
+pub struct Streak {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: i64,
+}
+
+#[derive(Serialize)]
+pub struct ItemStreak {
+    pub item_id: i32,
+    pub item_name: String,
+    #[serde(flatten)]
+    pub streak: Streak,
+}
+
+#[derive(Serialize)]
+pub struct Streaks {
+    /// Longest run of days on which anything was worn.
+    pub longest: Option<Streak>,
+    /// Longest run of every item, longest first.
+    pub items: Vec<ItemStreak>,
+}
+
+/// Finds the longest streak in `dates`, which have to be sorted. The earliest streak wins ties.
+fn longest_streak(dates: impl IntoIterator<Item = NaiveDate>) -> Option<Streak> {
+    let mut longest: Option<Streak> = None;
+    let mut current: Option<Streak> = None;
+
+    for date in dates {
+        let streak = match current {
+            Some(streak) if date == streak.end => streak,
+            Some(streak) if Some(date) == streak.end.succ_opt() => Streak {
+                end: date,
+                days: streak.days + 1,
+                ..streak
+            },
+            _ => Streak {
+                start: date,
+                end: date,
+                days: 1,
+            },
+        };
+
+        if longest.is_none_or(|longest| streak.days > longest.days) {
+            longest = Some(streak);
+        }
+        current = Some(streak);
+    }
+
+    longest
+}
+
+#[get("/analytics/streaks?<from>&<to>&<limit>")]
+pub(crate) async fn streaks(
+    user: UserLoggedIn,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+    conn: DbConn,
+) -> Result<Json<Streaks>, ErrorResponse> {
+    let range = DateRange::parse(from, to)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    let uid = user.0.id;
+    let (item_list, wears) = conn
+        .run(move |c| {
+            Ok::<_, diesel::result::Error>((load_items(c, uid)?, load_wears(c, uid, range)?))
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load statistics".to_string())
+        })?;
+
+    let mut per_item = BTreeMap::<i32, Vec<NaiveDate>>::new();
+    for (iid, date) in &wears {
+        per_item.entry(*iid).or_default().push(*date);
+    }
+
+    let mut items = item_list
+        .into_iter()
+        .filter_map(|(iid, name)| {
+            let dates = per_item.remove(&iid)?;
+            longest_streak(dates).map(|streak| ItemStreak {
+                item_id: iid,
+                item_name: name,
+                streak,
+            })
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| {
+        b.streak
+            .days
+            .cmp(&a.streak.days)
+            .then_with(|| a.item_name.cmp(&b.item_name))
+    });
+    items.truncate(limit);
+
+    Ok(Json(Streaks {
+        longest: longest_streak(wears.into_iter().map(|(_, date)| date)),
+        items,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn streak(dates: &[u32]) -> Option<(u32, u32, i64)> {
+        longest_streak(dates.iter().map(|day| date(*day)))
+            .map(|streak| (streak.start.day(), streak.end.day(), streak.days))
+    }
+
+    #[test]
+    fn no_dates_no_streak() {
+        assert_eq!(streak(&[]), None);
+    }
+
+    #[test]
+    fn single_day() {
+        assert_eq!(streak(&[5]), Some((5, 5, 1)));
+    }
+
+    #[test]
+    fn finds_longest_run() {
+        assert_eq!(streak(&[1, 2, 5, 6, 7, 9]), Some((5, 7, 3)));
+    }
+
+    #[test]
+    fn several_wears_on_a_day_count_once() {
+        assert_eq!(streak(&[1, 1, 2, 2, 2, 3]), Some((1, 3, 3)));
+    }
+
+    #[test]
+    fn earliest_streak_wins_ties() {
+        assert_eq!(streak(&[1, 2, 4, 5]), Some((1, 2, 2)));
+    }
+
+    #[test]
+    fn streaks_cross_month_ends() {
+        let dates = [
+            NaiveDate::from_ymd_opt(2026, 9, 30).unwrap(),
+            date(1),
+            date(2),
+        ];
+        let streak = longest_streak(dates).unwrap();
+        assert_eq!(
+            (streak.start, streak.end, streak.days),
+            (dates[0], date(2), 3)
+        );
+    }
+}
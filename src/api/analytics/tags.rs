@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::api::analytics::queries::{load_wears, DateRange, WearTotals};
+use crate::api::item_management::tag_tree::TagTree;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct TagWears {
+    pub tag_id: i32,
+    pub tag_name: String,
+    pub parent_id: Option<i32>,
+    #[serde(flatten)]
+    pub totals: WearTotals,
+}
+
+/// Wears of the items carrying each tag, most worn tag first.
+///
+/// Statistics roll up the hierarchy, so a tag counts the items carrying any tag below it as well.
+/// An item with several tags of the same subtree counts once.
+#[get("/analytics/tags?<from>&<to>")]
+pub(crate) async fn tag_wears(
+    user: UserLoggedIn,
+    from: Option<String>,
+    to: Option<String>,
+    conn: DbConn,
+) -> Result<Json<Vec<TagWears>>, ErrorResponse> {
+    let range = DateRange::parse(from, to)?;
+
+    let uid = user.0.id;
+    let (tag_list, item_tags, wears) = conn
+        .run(move |c| {
+            let tag_list = {
+                use schema::tags::dsl::*;
+                tags.filter(user_id.eq(uid))
+                    .select((id, tag_name, parent_id))
+                    .load::<(i32, String, Option<i32>)>(c)
+            }?;
+            let item_tags = {
+                use schema::item_tags;
+                use schema::tags;
+                item_tags::table
+                    .inner_join(tags::table)
+                    .filter(tags::user_id.eq(uid))
+                    .select((item_tags::tag_id, item_tags::item_id))
+                    .load::<(i32, i32)>(c)
+            }?;
+
+            Ok::<_, diesel::result::Error>((tag_list, item_tags, load_wears(c, uid, range)?))
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load statistics".to_string())
+        })?;
+
+    let mut item_wears = HashMap::<i32, i64>::new();
+    for (iid, _) in wears {
+        *item_wears.entry(iid).or_default() += 1;
+    }
+
+    let tree = TagTree::new(tag_list.iter().map(|(tid, _, parent)| (*tid, *parent)));
+    let mut out = tag_list
+        .into_iter()
+        .map(|(tid, name, parent)| {
+            let subtree = tree.subtree(tid);
+            let tagged = item_tags
+                .iter()
+                .filter(|(tag, _)| subtree.contains(tag))
+                .map(|(_, iid)| *iid)
+                .collect::<HashSet<_>>();
+
+            TagWears {
+                tag_id: tid,
+                tag_name: name,
+                parent_id: parent,
+                totals: WearTotals::of(&tagged, &item_wears),
+            }
+        })
+        .collect::<Vec<_>>();
+    out.sort_by(|a, b| {
+        b.totals
+            .wears
+            .cmp(&a.totals.wears)
+            .then_with(|| a.tag_name.cmp(&b.tag_name))
+    });
+
+    Ok(Json(out))
+}
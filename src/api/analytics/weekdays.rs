@@ -0,0 +1,59 @@
+use crate::api::analytics::queries::{load_wears, DateRange};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use chrono::{Datelike, Weekday};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Serialize)]
+pub struct WeekdayWears {
+    /// `Mon` to `Sun`
+    pub weekday: String,
+    pub wears: i64,
+}
+
+#[get("/analytics/weekdays?<from>&<to>")]
+pub(crate) async fn weekday_wears(
+    user: UserLoggedIn,
+    from: Option<String>,
+    to: Option<String>,
+    conn: DbConn,
+) -> Result<Json<Vec<WeekdayWears>>, ErrorResponse> {
+    let range = DateRange::parse(from, to)?;
+
+    let uid = user.0.id;
+    let wears = conn
+        .run(move |c| load_wears(c, uid, range))
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load statistics".to_string())
+        })?;
+
+    let mut counts = [0; 7];
+    for (_, date) in wears {
+        counts[date.weekday().num_days_from_monday() as usize] += 1;
+    }
+
+    Ok(Json(
+        WEEKDAYS
+            .iter()
+            .zip(counts)
+            .map(|(weekday, wears)| WeekdayWears {
+                weekday: weekday.to_string(),
+                wears,
+            })
+            .collect(),
+    ))
+}
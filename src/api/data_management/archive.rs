@@ -0,0 +1,127 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// Identifies archives written by `export`.
+pub(crate) const ARCHIVE_FORMAT: &str = "track-wear-export";
+/// Bumped whenever the layout or the meaning of existing fields changes:
+///
+/// 1. Items, uses, inventory movements and tags
+/// 2. Item attributes
+/// 3. Outfits
+/// 4. Reasons, notes and values of inventory movements
+/// 5. Tag colors and icons
+/// 6. Tag parents
+/// 7. Smart tags
+pub(crate) const ARCHIVE_VERSION: u32 = 7;
+
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+pub(crate) const ITEMS_FILE: &str = "items";
+pub(crate) const USES_FILE: &str = "uses";
+pub(crate) const INVENTORY_FILE: &str = "item_inventory";
+pub(crate) const TAGS_FILE: &str = "tags";
+pub(crate) const ITEM_TAGS_FILE: &str = "item_tags";
+/// Outfit tables exist since version 3.
+pub(crate) const OUTFITS_FILE: &str = "outfits";
+pub(crate) const OUTFIT_ITEMS_FILE: &str = "outfit_items";
+pub(crate) const OUTFIT_WEARS_FILE: &str = "outfit_wears";
+/// Smart tags exist since version 7.
+pub(crate) const SMART_TAGS_FILE: &str = "smart_tags";
+
+/// Path of a table dump in the archive, `format` being `json` or `csv`.
+pub(crate) fn table_path(format: &str, table: &str) -> String {
+    format!("{}/{}.{}", format, table, format)
+}
+
+/// Path of an item's image in the archive.
+pub(crate) fn image_path(item_id: i32) -> String {
+    format!("images/{}", item_id)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: NaiveDateTime,
+    pub files: Vec<String>,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct ItemRow {
+    pub id: i32,
+    pub item_name: String,
+    // Missing from archives written before items had attributes
+    pub brand: Option<String>,
+    pub size: Option<String>,
+    pub color: Option<String>,
+    pub material: Option<String>,
+    pub purchase_date: Option<NaiveDate>,
+    pub purchase_price_cents: Option<i32>,
+    pub purchase_currency: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct UseRow {
+    pub id: i32,
+    pub item_id: i32,
+    pub date: NaiveDate,
+    pub outfit_wear_id: Option<i32>,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct InventoryRow {
+    pub id: i32,
+    pub item_id: i32,
+    pub movement: i32,
+    pub update_time: NaiveDateTime,
+    /// Empty in archives written before movements had reasons.
+    #[serde(default)]
+    pub reason: String,
+    pub note: Option<String>,
+    pub value_cents: Option<i32>,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct TagRow {
+    pub id: i32,
+    pub tag_name: String,
+    // Missing from archives written before tags had colors and icons
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct ItemTagRow {
+    pub id: i32,
+    pub item_id: i32,
+    pub tag_id: i32,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct OutfitRow {
+    pub id: i32,
+    pub outfit_name: String,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct OutfitItemRow {
+    pub id: i32,
+    pub outfit_id: i32,
+    pub item_id: i32,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct OutfitWearRow {
+    pub id: i32,
+    pub outfit_id: i32,
+    pub date: NaiveDate,
+}
+
+/// Expressions refer to tags by name, so they keep working with the imported tags.
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct SmartTagRow {
+    pub id: i32,
+    pub smart_tag_name: String,
+    pub expression: String,
+}
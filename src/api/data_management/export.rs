@@ -0,0 +1,263 @@
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use crate::api::data_management::archive::{
+    image_path, table_path, InventoryRow, ItemRow, ItemTagRow, Manifest, OutfitItemRow, OutfitRow,
+    OutfitWearRow, SmartTagRow, TagRow, UseRow, ARCHIVE_FORMAT, ARCHIVE_VERSION, INVENTORY_FILE,
+    ITEMS_FILE, ITEM_TAGS_FILE, MANIFEST_FILE, OUTFITS_FILE, OUTFIT_ITEMS_FILE, OUTFIT_WEARS_FILE,
+    SMART_TAGS_FILE, TAGS_FILE, USES_FILE,
+};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use crate::settings::Settings;
+use chrono::Local;
+use diesel::prelude::*;
+use rocket::http::{Header, Status};
+use rocket::tokio::task::spawn_blocking;
+use rocket::State;
+use serde::Serialize;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+#[derive(Responder)]
+#[response(content_type = "application/zip")]
+pub struct ExportArchive {
+    data: Vec<u8>,
+    disposition: Header<'static>,
+}
+
+struct Tables {
+    items: Vec<ItemRow>,
+    uses: Vec<UseRow>,
+    inventory: Vec<InventoryRow>,
+    tags: Vec<TagRow>,
+    item_tags: Vec<ItemTagRow>,
+    outfits: Vec<OutfitRow>,
+    outfit_items: Vec<OutfitItemRow>,
+    outfit_wears: Vec<OutfitWearRow>,
+    smart_tags: Vec<SmartTagRow>,
+}
+
+type Archive = ZipWriter<Cursor<Vec<u8>>>;
+
+fn write_file(zip: &mut Archive, name: &str, data: &[u8]) -> Result<(), String> {
+    zip.start_file(name, FileOptions::default())
+        .map_err(|err| err.to_string())?;
+    zip.write_all(data).map_err(|err| err.to_string())
+}
+
+/// Dumps a table both as JSON, for re-importing, and as CSV, for spreadsheets.
+fn write_table<T: Serialize>(
+    zip: &mut Archive,
+    files: &mut Vec<String>,
+    table: &str,
+    rows: &[T],
+) -> Result<(), String> {
+    let json_path = table_path("json", table);
+    let json = serde_json::to_vec_pretty(rows).map_err(|err| err.to_string())?;
+    write_file(zip, &json_path, &json)?;
+    files.push(json_path);
+
+    let csv_path = table_path("csv", table);
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row).map_err(|err| err.to_string())?;
+    }
+    let csv = writer.into_inner().map_err(|err| err.to_string())?;
+    write_file(zip, &csv_path, &csv)?;
+    files.push(csv_path);
+
+    Ok(())
+}
+
+fn build_archive(tables: Tables, image_folder: &Path) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut files = Vec::new();
+
+    write_table(&mut zip, &mut files, ITEMS_FILE, &tables.items)?;
+    write_table(&mut zip, &mut files, USES_FILE, &tables.uses)?;
+    write_table(&mut zip, &mut files, INVENTORY_FILE, &tables.inventory)?;
+    write_table(&mut zip, &mut files, TAGS_FILE, &tables.tags)?;
+    write_table(&mut zip, &mut files, ITEM_TAGS_FILE, &tables.item_tags)?;
+    write_table(&mut zip, &mut files, OUTFITS_FILE, &tables.outfits)?;
+    write_table(
+        &mut zip,
+        &mut files,
+        OUTFIT_ITEMS_FILE,
+        &tables.outfit_items,
+    )?;
+    write_table(
+        &mut zip,
+        &mut files,
+        OUTFIT_WEARS_FILE,
+        &tables.outfit_wears,
+    )?;
+    write_table(&mut zip, &mut files, SMART_TAGS_FILE, &tables.smart_tags)?;
+
+    for item in &tables.items {
+        // Items without an image on disk are exported without one
+        if let Ok(image) = fs::read(image_folder.join(item.id.to_string())) {
+            let path = image_path(item.id);
+            write_file(&mut zip, &path, &image)?;
+            files.push(path);
+        }
+    }
+
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: Local::now().naive_local(),
+        files,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|err| err.to_string())?;
+    write_file(&mut zip, MANIFEST_FILE, &manifest)?;
+
+    zip.finish()
+        .map(Cursor::into_inner)
+        .map_err(|err| err.to_string())
+}
+
+#[get("/export")]
+pub(crate) async fn export(
+    user: UserLoggedIn,
+    conn: DbConn,
+    settings: &State<Settings>,
+) -> Result<ExportArchive, ErrorResponse> {
+    let uid = user.0.id;
+
+    let tables = conn
+        .run(move |c| {
+            c.build_transaction()
+                .read_only()
+                .repeatable_read()
+                .run::<_, diesel::result::Error, _>(|| {
+                    let items = {
+                        use schema::items::dsl::*;
+                        items
+                            .filter(user_id.eq(uid))
+                            .order(id)
+                            .select((
+                                id,
+                                item_name,
+                                brand,
+                                size,
+                                color,
+                                material,
+                                purchase_date,
+                                purchase_price_cents,
+                                purchase_currency,
+                                notes,
+                            ))
+                            .load::<ItemRow>(c)
+                    }?;
+                    let item_ids = items.iter().map(|item| item.id).collect::<Vec<_>>();
+
+                    let uses = {
+                        use schema::uses::dsl::*;
+                        uses.filter(item_id.eq_any(&item_ids))
+                            .order(id)
+                            .load::<UseRow>(c)
+                    }?;
+                    let inventory = {
+                        use schema::item_inventory::dsl::*;
+                        item_inventory
+                            .filter(item_id.eq_any(&item_ids))
+                            .order(id)
+                            .select((
+                                id,
+                                item_id,
+                                movement,
+                                update_time,
+                                reason,
+                                note,
+                                value_cents,
+                            ))
+                            .load::<InventoryRow>(c)
+                    }?;
+                    let tags = {
+                        use schema::tags::dsl::*;
+                        tags.filter(user_id.eq(uid))
+                            .order(id)
+                            .select((id, tag_name, color, icon, parent_id))
+                            .load::<TagRow>(c)
+                    }?;
+                    let item_tags = {
+                        use schema::item_tags::dsl::*;
+                        item_tags
+                            .filter(item_id.eq_any(&item_ids))
+                            .order(id)
+                            .load::<ItemTagRow>(c)
+                    }?;
+                    let outfits = {
+                        use schema::outfits::dsl::*;
+                        outfits
+                            .filter(user_id.eq(uid))
+                            .order(id)
+                            .select((id, outfit_name))
+                            .load::<OutfitRow>(c)
+                    }?;
+                    let outfit_ids = outfits.iter().map(|outfit| outfit.id).collect::<Vec<_>>();
+                    let outfit_items = {
+                        use schema::outfit_items::dsl::*;
+                        outfit_items
+                            .filter(outfit_id.eq_any(&outfit_ids))
+                            .order(id)
+                            .load::<OutfitItemRow>(c)
+                    }?;
+                    let outfit_wears = {
+                        use schema::outfit_wears::dsl::*;
+                        outfit_wears
+                            .filter(outfit_id.eq_any(&outfit_ids))
+                            .order(id)
+                            .load::<OutfitWearRow>(c)
+                    }?;
+                    let smart_tags = {
+                        use schema::smart_tags::dsl::*;
+                        smart_tags
+                            .filter(user_id.eq(uid))
+                            .order(id)
+                            .select((id, smart_tag_name, expression))
+                            .load::<SmartTagRow>(c)
+                    }?;
+
+                    Ok(Tables {
+                        items,
+                        uses,
+                        inventory,
+                        tags,
+                        item_tags,
+                        outfits,
+                        outfit_items,
+                        outfit_wears,
+                        smart_tags,
+                    })
+                })
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load data".to_string()))?;
+
+    let image_folder = Path::new(&settings.image_folder).to_path_buf();
+    let data = spawn_blocking(move || build_archive(tables, &image_folder))
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|archive| archive)
+        .map_err(|err| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                format!("Couldn't create archive: {}", err),
+            )
+        })?;
+
+    let file_name = format!("track-wear-export-{}.zip", Local::now().format("%Y-%m-%d"));
+
+    Ok(ExportArchive {
+        data,
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", file_name),
+        ),
+    })
+}
@@ -0,0 +1,929 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+
+use crate::api::data_management::archive::{
+    image_path, table_path, InventoryRow, ItemRow, ItemTagRow, Manifest, OutfitItemRow, OutfitRow,
+    OutfitWearRow, SmartTagRow, TagRow, UseRow, ARCHIVE_FORMAT, ARCHIVE_VERSION, INVENTORY_FILE,
+    ITEMS_FILE, ITEM_TAGS_FILE, MANIFEST_FILE, OUTFITS_FILE, OUTFIT_ITEMS_FILE, OUTFIT_WEARS_FILE,
+    SMART_TAGS_FILE, TAGS_FILE, USES_FILE,
+};
+use crate::api::item_management::attributes;
+use crate::api::item_management::create_tag;
+use crate::api::item_management::models::MovementReason;
+use crate::api::item_management::tag_tree::TagTree;
+use crate::api::smart_tag_management::create::smart_tag_name;
+use crate::api::smart_tag_management::filter::parse;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use crate::settings::Settings;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::tokio::task::spawn_blocking;
+use rocket::State;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use zip::ZipArchive;
+
+#[derive(FromForm)]
+pub struct FormImport<'a> {
+    archive: TempFile<'a>,
+    /// Only reports what would be imported, without changing anything.
+    dry_run: bool,
+}
+
+#[derive(Serialize, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub items: usize,
+    pub uses: usize,
+    pub inventory_movements: usize,
+    pub tags: usize,
+    pub merged_tags: usize,
+    pub item_tags: usize,
+    pub outfits: usize,
+    pub outfit_wears: usize,
+    pub smart_tags: usize,
+    pub images: usize,
+    pub conflicts: Vec<String>,
+}
+
+struct Contents {
+    items: Vec<ItemRow>,
+    uses: Vec<UseRow>,
+    inventory: Vec<InventoryRow>,
+    tags: Vec<TagRow>,
+    item_tags: Vec<ItemTagRow>,
+    outfits: Vec<OutfitRow>,
+    outfit_items: Vec<OutfitItemRow>,
+    outfit_wears: Vec<OutfitWearRow>,
+    smart_tags: Vec<SmartTagRow>,
+    /// Images by the id the item had in the archive.
+    images: HashMap<i32, Vec<u8>>,
+}
+
+/// Uploads are only limited in their compressed size, so these keep a zip bomb from exhausting
+/// memory once unpacked.
+const MAX_ENTRIES: usize = 100_000;
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_TOTAL_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Postgres binds at most 65535 parameters per statement, so rows are inserted in chunks that stay
+/// below this many values.
+const MAX_INSERT_PARAMS: usize = 60_000;
+
+/// A zip archive that refuses to unpack more than `MAX_TOTAL_SIZE` in total.
+struct LimitedZip<R> {
+    zip: ZipArchive<R>,
+    remaining: u64,
+}
+
+fn read_file<R: Read + Seek>(zip: &mut LimitedZip<R>, name: &str) -> Result<Vec<u8>, String> {
+    let file = zip
+        .zip
+        .by_name(name)
+        .map_err(|_| format!("Archive is missing {}", name))?;
+    let too_large = |size: u64| {
+        if size > MAX_FILE_SIZE {
+            Some(format!("{} is larger than {} bytes", name, MAX_FILE_SIZE))
+        } else if size > zip.remaining {
+            Some(format!(
+                "Archive is larger than {} bytes unpacked",
+                MAX_TOTAL_SIZE
+            ))
+        } else {
+            None
+        }
+    };
+    if let Some(err) = too_large(file.size()) {
+        return Err(err);
+    }
+
+    // The size in the archive could be a lie, so never read more than the limit
+    let mut data = Vec::new();
+    file.take(MAX_FILE_SIZE.min(zip.remaining) + 1)
+        .read_to_end(&mut data)
+        .map_err(|err| format!("Couldn't read {}: {}", name, err))?;
+    if let Some(err) = too_large(data.len() as u64) {
+        return Err(err);
+    }
+    zip.remaining -= data.len() as u64;
+
+    Ok(data)
+}
+
+fn read_table<R: Read + Seek, T: DeserializeOwned>(
+    zip: &mut LimitedZip<R>,
+    table: &str,
+) -> Result<Vec<T>, String> {
+    let path = table_path("json", table);
+    serde_json::from_slice(&read_file(zip, &path)?)
+        .map_err(|err| format!("Couldn't parse {}: {}", path, err))
+}
+
+fn read_archive(path: &Path) -> Result<Contents, String> {
+    let file = fs::File::open(path).map_err(|err| err.to_string())?;
+    let zip = ZipArchive::new(file).map_err(|_| "Not a zip archive".to_string())?;
+    if zip.len() > MAX_ENTRIES {
+        return Err(format!("Archive has more than {} files", MAX_ENTRIES));
+    }
+    let mut zip = LimitedZip {
+        zip,
+        remaining: MAX_TOTAL_SIZE,
+    };
+
+    let manifest: Manifest = serde_json::from_slice(&read_file(&mut zip, MANIFEST_FILE)?)
+        .map_err(|err| format!("Couldn't parse manifest: {}", err))?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(format!("Unknown archive format {}", manifest.format));
+    }
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(format!(
+            "Archive version {} is newer than the supported version {}",
+            manifest.version, ARCHIVE_VERSION
+        ));
+    }
+
+    let items: Vec<ItemRow> = read_table(&mut zip, ITEMS_FILE)?;
+
+    let mut images = HashMap::new();
+    for item in &items {
+        let path = image_path(item.id);
+        if manifest.files.contains(&path) {
+            images.insert(item.id, read_file(&mut zip, &path)?);
+        }
+    }
+
+    let (outfits, outfit_items, outfit_wears) = if manifest.version >= 3 {
+        (
+            read_table(&mut zip, OUTFITS_FILE)?,
+            read_table(&mut zip, OUTFIT_ITEMS_FILE)?,
+            read_table(&mut zip, OUTFIT_WEARS_FILE)?,
+        )
+    } else {
+        (Vec::new(), Vec::new(), Vec::new())
+    };
+
+    let smart_tags: Vec<SmartTagRow> = if manifest.version >= 7 {
+        read_table(&mut zip, SMART_TAGS_FILE)?
+    } else {
+        Vec::new()
+    };
+    for smart_tag in &smart_tags {
+        parse(&smart_tag.expression).map_err(|err| {
+            format!(
+                "Invalid expression of smart tag {}: {}",
+                smart_tag.smart_tag_name, err
+            )
+        })?;
+    }
+
+    let mut inventory: Vec<InventoryRow> = read_table(&mut zip, INVENTORY_FILE)?;
+    for row in &mut inventory {
+        if row.reason.is_empty() {
+            row.reason = if row.movement > 0 {
+                MovementReason::Purchased.as_str().to_string()
+            } else {
+                MovementReason::Correction.as_str().to_string()
+            };
+        } else if MovementReason::parse(&row.reason).is_none() {
+            return Err(format!("Unknown inventory reason {}", row.reason));
+        }
+    }
+
+    Ok(Contents {
+        uses: read_table(&mut zip, USES_FILE)?,
+        inventory,
+        tags: read_table(&mut zip, TAGS_FILE)?,
+        item_tags: read_table(&mut zip, ITEM_TAGS_FILE)?,
+        outfits,
+        outfit_items,
+        outfit_wears,
+        smart_tags,
+        items,
+        images,
+    })
+}
+
+/// The parent of every tag once tags with the same name are merged, which is how they are
+/// imported. Each name is represented by the id of its first tag and keeps the first parent any of
+/// its tags has.
+fn merged_tag_parents(tags: &[TagRow]) -> HashMap<i32, Option<i32>> {
+    let mut first_of_name = HashMap::<&str, i32>::new();
+    for tag in tags {
+        first_of_name.entry(&tag.tag_name).or_insert(tag.id);
+    }
+    let merged_ids = tags
+        .iter()
+        .map(|tag| (tag.id, first_of_name[tag.tag_name.as_str()]))
+        .collect::<HashMap<_, _>>();
+
+    let mut parents = HashMap::new();
+    for tag in tags {
+        let parent = tag
+            .parent_id
+            .and_then(|parent| merged_ids.get(&parent).copied());
+        let merged_parent = parents.entry(merged_ids[&tag.id]).or_insert(None);
+        if merged_parent.is_none() {
+            *merged_parent = parent;
+        }
+    }
+    parents
+}
+
+/// Runs the values of the archive through the checks of the endpoints that create them, and
+/// normalizes them the same way, so an import only stores what the API would have accepted.
+fn check_values(contents: &mut Contents) -> Result<(), String> {
+    for item in &mut contents.items {
+        let invalid = |err: ErrorResponse| format!("Invalid item {}: {}", item.id, err.message());
+        for value in [
+            &mut item.brand,
+            &mut item.size,
+            &mut item.color,
+            &mut item.material,
+            &mut item.notes,
+        ] {
+            *value = attributes::text(value.take()).flatten();
+        }
+        item.purchase_price_cents =
+            attributes::price(item.purchase_price_cents.map(|cents| cents.to_string()))
+                .map_err(invalid)?
+                .flatten();
+        item.purchase_currency = attributes::currency(item.purchase_currency.take())
+            .map_err(invalid)?
+            .flatten();
+    }
+
+    for row in &contents.inventory {
+        let reason = MovementReason::parse(&row.reason)
+            .ok_or_else(|| format!("Unknown inventory reason {}", row.reason))?;
+        if !reason.allows(row.movement) {
+            return Err(format!(
+                "Invalid inventory movement {}: Movement {} doesn't match reason {}",
+                row.id, row.movement, row.reason
+            ));
+        }
+        if row.value_cents.is_some_and(|value| value < 0) {
+            return Err(format!(
+                "Invalid inventory movement {}: Value must not be negative",
+                row.id
+            ));
+        }
+    }
+
+    for tag in &mut contents.tags {
+        let invalid = |err: ErrorResponse| format!("Invalid tag {}: {}", tag.id, err.message());
+        tag.tag_name = create_tag::tag_name(tag.tag_name.clone()).map_err(invalid)?;
+        tag.color = create_tag::color(tag.color.take())
+            .map_err(invalid)?
+            .flatten();
+        tag.icon = attributes::text(tag.icon.take()).flatten();
+    }
+
+    for smart_tag in &mut contents.smart_tags {
+        smart_tag.smart_tag_name = smart_tag_name(smart_tag.smart_tag_name.clone())
+            .map_err(|err| format!("Invalid smart tag {}: {}", smart_tag.id, err.message()))?;
+    }
+
+    Ok(())
+}
+
+/// Rejects rows pointing at items, tags or outfits that aren't part of the archive.
+fn check_references(contents: &Contents) -> Result<(), String> {
+    let item_ids = contents
+        .items
+        .iter()
+        .map(|item| item.id)
+        .collect::<HashSet<_>>();
+    let tag_ids = contents
+        .tags
+        .iter()
+        .map(|tag| tag.id)
+        .collect::<HashSet<_>>();
+    let outfit_ids = contents
+        .outfits
+        .iter()
+        .map(|outfit| outfit.id)
+        .collect::<HashSet<_>>();
+    let wear_ids = contents
+        .outfit_wears
+        .iter()
+        .map(|wear| wear.id)
+        .collect::<HashSet<_>>();
+
+    let unknown_item = contents
+        .uses
+        .iter()
+        .map(|row| row.item_id)
+        .chain(contents.inventory.iter().map(|row| row.item_id))
+        .chain(contents.item_tags.iter().map(|row| row.item_id))
+        .chain(contents.outfit_items.iter().map(|row| row.item_id))
+        .find(|iid| !item_ids.contains(iid));
+    if let Some(iid) = unknown_item {
+        return Err(format!("Archive references unknown item {}", iid));
+    }
+
+    if let Some(row) = contents
+        .item_tags
+        .iter()
+        .find(|row| !tag_ids.contains(&row.tag_id))
+    {
+        return Err(format!("Archive references unknown tag {}", row.tag_id));
+    }
+    if let Some(parent) = contents
+        .tags
+        .iter()
+        .filter_map(|tag| tag.parent_id)
+        .find(|parent| !tag_ids.contains(parent))
+    {
+        return Err(format!("Archive references unknown tag {}", parent));
+    }
+    if let Some(tid) = TagTree::new(merged_tag_parents(&contents.tags)).find_cycle() {
+        return Err(format!("Tag {} is its own ancestor", tid));
+    }
+
+    let unknown_outfit = contents
+        .outfit_items
+        .iter()
+        .map(|row| row.outfit_id)
+        .chain(contents.outfit_wears.iter().map(|row| row.outfit_id))
+        .find(|oid| !outfit_ids.contains(oid));
+    if let Some(oid) = unknown_outfit {
+        return Err(format!("Archive references unknown outfit {}", oid));
+    }
+
+    if let Some(wid) = contents
+        .uses
+        .iter()
+        .filter_map(|row| row.outfit_wear_id)
+        .find(|wid| !wear_ids.contains(wid))
+    {
+        return Err(format!("Archive references unknown outfit wear {}", wid));
+    }
+
+    Ok(())
+}
+
+/// Recreates the archive's data under `uid`, returning the new id of every imported item.
+///
+/// Tags are matched by name, so importing into an account that already has a tag reuses it and
+/// keeps its color, icon and place in the hierarchy.
+fn insert_contents(
+    c: &PgConnection,
+    uid: i32,
+    contents: &Contents,
+    existing_tags: &HashMap<String, i32>,
+) -> QueryResult<HashMap<i32, i32>> {
+    c.transaction(|| {
+        let mut existing_tags = existing_tags.clone();
+        let mut created_tags = HashSet::new();
+        let mut tag_map = HashMap::new();
+        for tag in &contents.tags {
+            let new_id = match existing_tags.get(&tag.tag_name) {
+                Some(existing) => *existing,
+                None => {
+                    use schema::tags::dsl::*;
+                    let new_id = diesel::insert_into(tags)
+                        .values((
+                            user_id.eq(uid),
+                            tag_name.eq(&tag.tag_name),
+                            color.eq(&tag.color),
+                            icon.eq(&tag.icon),
+                        ))
+                        .returning(id)
+                        .get_result::<i32>(c)?;
+                    // Older archives may name several tags the same
+                    existing_tags.insert(tag.tag_name.clone(), new_id);
+                    created_tags.insert(new_id);
+                    new_id
+                }
+            };
+            tag_map.insert(tag.id, new_id);
+        }
+        for (tid, parent) in merged_tag_parents(&contents.tags) {
+            let new_id = tag_map[&tid];
+            if let Some(parent) = parent.filter(|_| created_tags.contains(&new_id)) {
+                use schema::tags::dsl::*;
+                diesel::update(tags.filter(id.eq(new_id)))
+                    .set(parent_id.eq(tag_map[&parent]))
+                    .execute(c)?;
+            }
+        }
+
+        let mut item_map = HashMap::new();
+        for item in &contents.items {
+            use schema::items::dsl::*;
+            let new_id = diesel::insert_into(items)
+                .values((
+                    user_id.eq(uid),
+                    item_name.eq(&item.item_name),
+                    brand.eq(&item.brand),
+                    size.eq(&item.size),
+                    color.eq(&item.color),
+                    material.eq(&item.material),
+                    purchase_date.eq(item.purchase_date),
+                    purchase_price_cents.eq(item.purchase_price_cents),
+                    purchase_currency.eq(&item.purchase_currency),
+                    notes.eq(&item.notes),
+                ))
+                .returning(id)
+                .get_result::<i32>(c)?;
+            item_map.insert(item.id, new_id);
+        }
+
+        let mut outfit_map = HashMap::new();
+        for outfit in &contents.outfits {
+            use schema::outfits::dsl::*;
+            let new_id = diesel::insert_into(outfits)
+                .values((user_id.eq(uid), outfit_name.eq(&outfit.outfit_name)))
+                .returning(id)
+                .get_result::<i32>(c)?;
+            outfit_map.insert(outfit.id, new_id);
+        }
+        {
+            use schema::outfit_items::dsl::*;
+            let rows = contents
+                .outfit_items
+                .iter()
+                .map(|row| {
+                    (
+                        outfit_id.eq(outfit_map[&row.outfit_id]),
+                        item_id.eq(item_map[&row.item_id]),
+                    )
+                })
+                .collect::<Vec<_>>();
+            for chunk in rows.chunks(MAX_INSERT_PARAMS / 2) {
+                diesel::insert_into(outfit_items)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+                    .execute(c)?;
+            }
+        }
+        let mut wear_map = HashMap::new();
+        for wear in &contents.outfit_wears {
+            use schema::outfit_wears::dsl::*;
+            let new_id = diesel::insert_into(outfit_wears)
+                .values((
+                    outfit_id.eq(outfit_map[&wear.outfit_id]),
+                    date.eq(wear.date),
+                ))
+                .returning(id)
+                .get_result::<i32>(c)?;
+            wear_map.insert(wear.id, new_id);
+        }
+
+        {
+            use schema::uses::dsl::*;
+            let rows = contents
+                .uses
+                .iter()
+                .map(|row| {
+                    (
+                        item_id.eq(item_map[&row.item_id]),
+                        date.eq(row.date),
+                        outfit_wear_id.eq(row.outfit_wear_id.map(|wid| wear_map[&wid])),
+                    )
+                })
+                .collect::<Vec<_>>();
+            for chunk in rows.chunks(MAX_INSERT_PARAMS / 3) {
+                diesel::insert_into(uses).values(chunk).execute(c)?;
+            }
+        }
+        {
+            use schema::item_inventory::dsl::*;
+            let rows = contents
+                .inventory
+                .iter()
+                .map(|row| {
+                    (
+                        item_id.eq(item_map[&row.item_id]),
+                        movement.eq(row.movement),
+                        update_time.eq(row.update_time),
+                        reason.eq(&row.reason),
+                        note.eq(&row.note),
+                        value_cents.eq(row.value_cents),
+                    )
+                })
+                .collect::<Vec<_>>();
+            for chunk in rows.chunks(MAX_INSERT_PARAMS / 6) {
+                diesel::insert_into(item_inventory)
+                    .values(chunk)
+                    .execute(c)?;
+            }
+        }
+        {
+            use schema::item_tags::dsl::*;
+            let rows = contents
+                .item_tags
+                .iter()
+                .map(|row| {
+                    (
+                        item_id.eq(item_map[&row.item_id]),
+                        tag_id.eq(tag_map[&row.tag_id]),
+                    )
+                })
+                .collect::<Vec<_>>();
+            for chunk in rows.chunks(MAX_INSERT_PARAMS / 2) {
+                diesel::insert_into(item_tags)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+                    .execute(c)?;
+            }
+        }
+        {
+            use schema::smart_tags::dsl::*;
+            let rows = contents
+                .smart_tags
+                .iter()
+                .map(|row| {
+                    (
+                        user_id.eq(uid),
+                        smart_tag_name.eq(&row.smart_tag_name),
+                        expression.eq(&row.expression),
+                    )
+                })
+                .collect::<Vec<_>>();
+            // Smart tags the account already has a namesake of are skipped
+            for chunk in rows.chunks(MAX_INSERT_PARAMS / 3) {
+                diesel::insert_into(smart_tags)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+                    .execute(c)?;
+            }
+        }
+
+        Ok(item_map)
+    })
+}
+
+#[post("/import", data = "<form_import>")]
+pub(crate) async fn import(
+    form_import: Form<FormImport<'_>>,
+    user: UserLoggedIn,
+    conn: DbConn,
+    settings: &State<Settings>,
+) -> Result<Json<ImportReport>, ErrorResponse> {
+    let archive_path = form_import
+        .archive
+        .path()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| {
+            ErrorResponse::new(Status { code: 400 }, "Couldn't read archive".to_string())
+        })?;
+
+    let contents = spawn_blocking(move || read_archive(&archive_path))
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|contents| contents)
+        .and_then(|mut contents| check_values(&mut contents).map(|_| contents))
+        .and_then(|contents| check_references(&contents).map(|_| contents))
+        .map_err(|err| ErrorResponse::new(Status { code: 400 }, err))?;
+
+    let uid = user.0.id;
+    let (existing_tags, existing_items, existing_smart_tags) = conn
+        .run(move |c| {
+            let existing_tags = {
+                use schema::tags::dsl::*;
+                tags.filter(user_id.eq(uid))
+                    .select((tag_name, id))
+                    .load::<(String, i32)>(c)
+            }?;
+            let existing_items = {
+                use schema::items::dsl::*;
+                items
+                    .filter(user_id.eq(uid))
+                    .select(item_name)
+                    .load::<String>(c)
+            }?;
+            let existing_smart_tags = {
+                use schema::smart_tags::dsl::*;
+                smart_tags
+                    .filter(user_id.eq(uid))
+                    .select(smart_tag_name)
+                    .load::<String>(c)
+            }?;
+
+            Ok::<_, diesel::result::Error>((
+                existing_tags.into_iter().collect::<HashMap<_, _>>(),
+                existing_items.into_iter().collect::<HashSet<_>>(),
+                existing_smart_tags.into_iter().collect::<HashSet<_>>(),
+            ))
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load data".to_string()))?;
+
+    let mut report = ImportReport {
+        dry_run: form_import.dry_run,
+        items: contents.items.len(),
+        uses: contents.uses.len(),
+        inventory_movements: contents.inventory.len(),
+        item_tags: contents.item_tags.len(),
+        outfits: contents.outfits.len(),
+        outfit_wears: contents.outfit_wears.len(),
+        images: contents.images.len(),
+        ..ImportReport::default()
+    };
+    let mut archive_tags = HashSet::new();
+    for tag in &contents.tags {
+        if !archive_tags.insert(&tag.tag_name) {
+            report.merged_tags += 1;
+        } else if existing_tags.contains_key(&tag.tag_name) {
+            report.merged_tags += 1;
+            report.conflicts.push(format!(
+                "Tag {} already exists and will be merged",
+                tag.tag_name
+            ));
+        } else {
+            report.tags += 1;
+        }
+    }
+    for item in &contents.items {
+        if existing_items.contains(&item.item_name) {
+            report.conflicts.push(format!(
+                "Item {} already exists and will be imported as a separate item",
+                item.item_name
+            ));
+        }
+    }
+
+    let mut archive_smart_tags = HashSet::new();
+    for smart_tag in &contents.smart_tags {
+        if !archive_smart_tags.insert(&smart_tag.smart_tag_name) {
+            continue;
+        }
+        if existing_smart_tags.contains(&smart_tag.smart_tag_name) {
+            report.conflicts.push(format!(
+                "Smart tag {} already exists and will be skipped",
+                smart_tag.smart_tag_name
+            ));
+        } else {
+            report.smart_tags += 1;
+        }
+    }
+
+    if form_import.dry_run {
+        return Ok(Json(report));
+    }
+
+    let (item_map, contents) = conn
+        .run(move |c| insert_contents(c, uid, &contents, &existing_tags).map(|map| (map, contents)))
+        .await
+        .map_err(|err| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                format!("Couldn't import data: {}", err),
+            )
+        })?;
+
+    let image_folder = PathBuf::from(&settings.image_folder);
+    for (old_id, image) in &contents.images {
+        let image_file = image_folder.join(item_map[old_id].to_string());
+        if let Err(err) = rocket::tokio::fs::write(image_file, image).await {
+            report.images -= 1;
+            report.conflicts.push(format!(
+                "Couldn't restore image of item {}: {}",
+                item_map[old_id], err
+            ));
+        }
+    }
+
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn rows<T: DeserializeOwned>(tables: &Value, table: &str) -> Vec<T> {
+        serde_json::from_value(tables.get(table).cloned().unwrap_or_else(|| json!([]))).unwrap()
+    }
+
+    /// Archive contents with the given tables, all others empty.
+    fn contents(tables: Value) -> Contents {
+        Contents {
+            items: rows(&tables, "items"),
+            uses: rows(&tables, "uses"),
+            inventory: rows(&tables, "inventory"),
+            tags: rows(&tables, "tags"),
+            item_tags: rows(&tables, "item_tags"),
+            outfits: rows(&tables, "outfits"),
+            outfit_items: rows(&tables, "outfit_items"),
+            outfit_wears: rows(&tables, "outfit_wears"),
+            smart_tags: rows(&tables, "smart_tags"),
+            images: HashMap::new(),
+        }
+    }
+
+    fn valid() -> Value {
+        json!({
+            "items": [{"id": 1, "item_name": "Shirt"}, {"id": 2, "item_name": "Jeans"}],
+            "uses": [
+                {"id": 1, "item_id": 1, "date": "2026-10-01", "outfit_wear_id": 1},
+                {"id": 2, "item_id": 2, "date": "2026-10-02"},
+            ],
+            "inventory": [{
+                "id": 1, "item_id": 2, "movement": 1, "update_time": "2026-10-01T12:00:00",
+                "reason": "purchased",
+            }],
+            "tags": [
+                {"id": 1, "tag_name": "Tops"},
+                {"id": 2, "tag_name": "Shirts", "parent_id": 1},
+            ],
+            "item_tags": [{"id": 1, "item_id": 1, "tag_id": 2}],
+            "outfits": [{"id": 1, "outfit_name": "Casual"}],
+            "outfit_items": [{"id": 1, "outfit_id": 1, "item_id": 1}],
+            "outfit_wears": [{"id": 1, "outfit_id": 1, "date": "2026-10-01"}],
+        })
+    }
+
+    fn check(change: impl FnOnce(&mut Value)) -> Result<(), String> {
+        let mut tables = valid();
+        change(&mut tables);
+        check_references(&contents(tables))
+    }
+
+    fn check_value(change: impl FnOnce(&mut Value)) -> Result<Contents, String> {
+        let mut tables = valid();
+        change(&mut tables);
+        let mut contents = contents(tables);
+        check_values(&mut contents).map(|_| contents)
+    }
+
+    #[test]
+    fn accepts_consistent_archive() {
+        assert!(check_value(|_| {}).is_ok());
+        assert_eq!(check(|_| {}), Ok(()));
+        assert_eq!(check_references(&contents(json!({}))), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unknown_items() {
+        assert_eq!(
+            check(|tables| tables["uses"][1]["item_id"] = json!(3)),
+            Err("Archive references unknown item 3".to_string())
+        );
+        assert_eq!(
+            check(|tables| tables["inventory"][0]["item_id"] = json!(4)),
+            Err("Archive references unknown item 4".to_string())
+        );
+        assert_eq!(
+            check(|tables| tables["outfit_items"][0]["item_id"] = json!(5)),
+            Err("Archive references unknown item 5".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_tags() {
+        assert_eq!(
+            check(|tables| tables["item_tags"][0]["tag_id"] = json!(3)),
+            Err("Archive references unknown tag 3".to_string())
+        );
+        assert_eq!(
+            check(|tables| tables["tags"][1]["parent_id"] = json!(4)),
+            Err("Archive references unknown tag 4".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_tag_cycles() {
+        // Either tag of the cycle may be reported
+        let err = check(|tables| tables["tags"][0]["parent_id"] = json!(2)).unwrap_err();
+        assert!(
+            err == "Tag 1 is its own ancestor" || err == "Tag 2 is its own ancestor",
+            "{}",
+            err
+        );
+        assert_eq!(
+            check(|tables| tables["tags"][0]["parent_id"] = json!(1)),
+            Err("Tag 1 is its own ancestor".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_cycles_through_tags_merged_by_name() {
+        // Tops > Shirts > Tops, once both tags named Tops are one tag
+        let err = check(|tables| {
+            tables["tags"]
+                .as_array_mut()
+                .unwrap()
+                .push(json!({"id": 3, "tag_name": "Tops", "parent_id": 2}))
+        })
+        .unwrap_err();
+        assert!(err.ends_with("is its own ancestor"), "{}", err);
+
+        // The second Shirts would be its own parent, but the first one's parent wins
+        assert_eq!(
+            check(|tables| {
+                tables["tags"]
+                    .as_array_mut()
+                    .unwrap()
+                    .push(json!({"id": 3, "tag_name": "Shirts", "parent_id": 2}))
+            }),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn merged_tags_keep_the_first_parent() {
+        let tags = rows::<TagRow>(
+            &json!({"tags": [
+                {"id": 1, "tag_name": "Tops"},
+                {"id": 2, "tag_name": "Shirts"},
+                {"id": 3, "tag_name": "Shirts", "parent_id": 1},
+                {"id": 4, "tag_name": "Oxford", "parent_id": 3},
+                {"id": 5, "tag_name": "Shirts", "parent_id": 4},
+            ]}),
+            "tags",
+        );
+        assert_eq!(
+            merged_tag_parents(&tags),
+            HashMap::from([(1, None), (2, Some(1)), (4, Some(2))])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_outfits_and_wears() {
+        assert_eq!(
+            check(|tables| tables["outfit_wears"][0]["outfit_id"] = json!(2)),
+            Err("Archive references unknown outfit 2".to_string())
+        );
+        assert_eq!(
+            check(|tables| tables["uses"][0]["outfit_wear_id"] = json!(2)),
+            Err("Archive references unknown outfit wear 2".to_string())
+        );
+    }
+
+    #[test]
+    fn normalizes_values_like_the_api() {
+        let contents = check_value(|tables| {
+            tables["items"][0]["brand"] = json!("  Acme ");
+            tables["items"][0]["notes"] = json!(" ");
+            tables["items"][0]["purchase_currency"] = json!("eur");
+            tables["tags"][0]["tag_name"] = json!(" Tops ");
+            tables["tags"][0]["color"] = json!("1E90FF");
+        })
+        .unwrap();
+        assert_eq!(contents.items[0].brand.as_deref(), Some("Acme"));
+        assert_eq!(contents.items[0].notes, None);
+        assert_eq!(contents.items[0].purchase_currency.as_deref(), Some("EUR"));
+        assert_eq!(contents.tags[0].tag_name, "Tops");
+        assert_eq!(contents.tags[0].color.as_deref(), Some("#1e90ff"));
+    }
+
+    #[test]
+    fn rejects_values_the_api_would_reject() {
+        let err = |change: fn(&mut Value)| check_value(change).err();
+        assert_eq!(
+            err(|tables| tables["items"][1]["purchase_price_cents"] = json!(-1)),
+            Some("Invalid item 2: Invalid price -1, expected a number of cents".to_string())
+        );
+        assert_eq!(
+            err(|tables| tables["items"][0]["purchase_currency"] = json!("Euro")),
+            Some("Invalid item 1: Invalid currency Euro, expected a code like EUR".to_string())
+        );
+        assert_eq!(
+            err(|tables| tables["tags"][1]["tag_name"] = json!(" ")),
+            Some("Invalid tag 2: Tag name must not be empty".to_string())
+        );
+        assert_eq!(
+            err(|tables| tables["tags"][0]["color"] = json!("#1e90ff00")),
+            Some(
+                "Invalid tag 1: Invalid color #1e90ff00, expected a hex code like #1e90ff"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            err(|tables| tables["smart_tags"] = json!([
+                {"id": 4, "smart_tag_name": "", "expression": "tag:Tops"},
+            ])),
+            Some("Invalid smart tag 4: Smart tag name must not be empty".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_movements_against_their_reason() {
+        let err = |change: fn(&mut Value)| check_value(change).err();
+        assert_eq!(
+            err(|tables| tables["inventory"][0]["movement"] = json!(-1)),
+            Some(
+                "Invalid inventory movement 1: Movement -1 doesn't match reason purchased"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            err(|tables| tables["inventory"][0]["value_cents"] = json!(-5)),
+            Some("Invalid inventory movement 1: Value must not be negative".to_string())
+        );
+        assert_eq!(
+            err(|tables| tables["inventory"][0]["reason"] = json!("misplaced")),
+            Some("Unknown inventory reason misplaced".to_string())
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod archive;
+pub(crate) mod export;
+pub(crate) mod import;
@@ -18,6 +18,37 @@ pub struct FormTag {
     tag_id: i32,
 }
 
+/// Tags every one of `item_ids` with every one of `tag_ids`, returning the added
+/// `(item_id, tag_id)` pairs. Items and tags of other users as well as pairs that already exist
+/// are skipped.
+pub(super) fn insert_item_tags(
+    c: &PgConnection,
+    uid: i32,
+    item_ids: &[i32],
+    tag_ids: &[i32],
+) -> QueryResult<Vec<(i32, i32)>> {
+    use schema::item_tags;
+    use schema::items;
+    use schema::tags;
+    use schema::users;
+
+    let pairs = users::table
+        .filter(users::id.eq(uid))
+        .inner_join(items::table.on(items::user_id.eq(users::id)))
+        .filter(items::id.eq_any(item_ids))
+        .inner_join(tags::table.on(tags::user_id.eq(users::id)))
+        .filter(tags::id.eq_any(tag_ids))
+        .select((items::id, tags::id));
+
+    diesel::insert_into(item_tags::table)
+        .values(pairs)
+        .into_columns((item_tags::item_id, item_tags::tag_id))
+        .on_conflict_do_nothing()
+        .returning((item_tags::item_id, item_tags::tag_id))
+        .get_results(c)
+}
+
+/// Adding a tag the item already has does nothing.
 #[post("/item/<item>/add_tag", data = "<form_tag>")]
 pub(crate) async fn add_tag(
     item: i32,
@@ -25,27 +56,9 @@ pub(crate) async fn add_tag(
     conn: DbConn,
     form_tag: Form<FormTag>,
 ) -> Result<(), ErrorResponse> {
-    conn.run(move |c| {
-        use schema::item_tags;
-        use schema::items;
-        use schema::tags;
-        use schema::users;
-
-        let pair = users::table
-            .filter(users::id.eq(user.0.id))
-            .inner_join(items::table.on(items::user_id.eq(users::id)))
-            .filter(items::id.eq(item))
-            .inner_join(tags::table.on(tags::user_id.eq(users::id)))
-            .filter(tags::id.eq(form_tag.tag_id))
-            .select((items::id, tags::id));
-
-        diesel::insert_into(item_tags::table)
-            .values(pair)
-            .into_columns((item_tags::item_id, item_tags::tag_id))
-            .execute(c)
-    })
-    .await
-    .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't add tag".to_string()))?;
+    conn.run(move |c| insert_item_tags(c, user.0.id, &[item], &[form_tag.tag_id]))
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't add tag".to_string()))?;
 
     Ok(())
 }
@@ -1,24 +1,42 @@
+use crate::api::item_management::attributes::use_date;
+use crate::api::item_management::get_uses::UseOut;
 use crate::api::item_management::models::Item;
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
 use crate::schema;
 use crate::schema::uses;
+use chrono::NaiveDate;
 use diesel::prelude::*;
+use rocket::form::Form;
 use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[derive(FromForm)]
+pub struct FormUse {
+    /// Defaults to today.
+    date: Option<String>,
+}
 
 #[derive(Insertable, AsChangeset)]
 #[table_name = "uses"]
 struct NewUse {
     item_id: i32,
+    date: Option<NaiveDate>,
 }
 
-#[post("/item/<item>/add_use")]
+#[post("/item/<item>/add_use", data = "<form_use>")]
 pub(crate) async fn add_use(
     item: i32,
+    form_use: Option<Form<FormUse>>,
     user: UserLoggedIn,
     conn: DbConn,
-) -> Result<(), ErrorResponse> {
+) -> Result<Json<UseOut>, ErrorResponse> {
+    let new_date = form_use
+        .and_then(|form_use| form_use.into_inner().date)
+        .map(|form_date| use_date(&form_date))
+        .transpose()?;
+
     use schema::items::dsl::*;
 
     let item_list = conn
@@ -39,20 +57,24 @@ pub(crate) async fn add_use(
 
     use schema::uses::dsl::*;
 
-    let us = NewUse { item_id: item };
-
-    conn.run(move |c| {
-        diesel::insert_into(uses)
-            .values(&us)
-            .execute(c)
-            .map_err(|err| {
-                ErrorResponse::new(
-                    Status { code: 500 },
-                    format!("Couldn't update use: {}", err),
-                )
-            })
-    })
-    .await?;
-
-    Ok(())
+    let us = NewUse {
+        item_id: item,
+        date: new_date,
+    };
+
+    let new_use = conn
+        .run(move |c| {
+            diesel::insert_into(uses)
+                .values(&us)
+                .get_result::<UseOut>(c)
+                .map_err(|err| {
+                    ErrorResponse::new(
+                        Status { code: 500 },
+                        format!("Couldn't update use: {}", err),
+                    )
+                })
+        })
+        .await?;
+
+    Ok(Json(new_use))
 }
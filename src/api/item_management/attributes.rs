@@ -0,0 +1,83 @@
+use crate::error::ErrorResponse;
+use chrono::{Local, NaiveDate};
+use rocket::http::Status;
+
+// The optional attributes of an item arrive as plain form values. `None` means a field wasn't
+// sent, `Some(None)` that it was sent empty to clear the attribute.
+
+/// Dates are exchanged as `YYYY-MM-DD`.
+pub(crate) fn parse_date(value: &str) -> Result<NaiveDate, ErrorResponse> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        ErrorResponse::new(
+            Status { code: 400 },
+            format!("Invalid date {}, expected YYYY-MM-DD", value),
+        )
+    })
+}
+
+/// Uses can be backdated but not logged ahead, everything reading the use history relies on them
+/// lying at or before today.
+pub(crate) fn use_date(value: &str) -> Result<NaiveDate, ErrorResponse> {
+    let date = parse_date(value)?;
+    if date > Local::now().date_naive() {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            format!("Date {} is in the future", date),
+        ));
+    }
+    Ok(date)
+}
+
+pub(crate) fn text(value: Option<String>) -> Option<Option<String>> {
+    value.map(|value| {
+        let value = value.trim();
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    })
+}
+
+pub(super) fn date(value: Option<String>) -> Result<Option<Option<NaiveDate>>, ErrorResponse> {
+    text(value)
+        .map(|value| value.map(|value| parse_date(&value)).transpose())
+        .transpose()
+}
+
+/// Prices are whole cents of `purchase_currency`.
+pub(crate) fn price(value: Option<String>) -> Result<Option<Option<i32>>, ErrorResponse> {
+    text(value)
+        .map(|value| {
+            value
+                .map(|value| match value.parse::<i32>() {
+                    Ok(cents) if cents >= 0 => Ok(cents),
+                    _ => Err(ErrorResponse::new(
+                        Status { code: 400 },
+                        format!("Invalid price {}, expected a number of cents", value),
+                    )),
+                })
+                .transpose()
+        })
+        .transpose()
+}
+
+/// Currencies are ISO 4217 codes like `EUR`.
+pub(crate) fn currency(value: Option<String>) -> Result<Option<Option<String>>, ErrorResponse> {
+    text(value)
+        .map(|value| {
+            value
+                .map(|value| {
+                    if value.len() == 3 && value.chars().all(|c| c.is_ascii_alphabetic()) {
+                        Ok(value.to_ascii_uppercase())
+                    } else {
+                        Err(ErrorResponse::new(
+                            Status { code: 400 },
+                            format!("Invalid currency {}, expected a code like EUR", value),
+                        ))
+                    }
+                })
+                .transpose()
+        })
+        .transpose()
+}
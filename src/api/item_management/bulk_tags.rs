@@ -0,0 +1,106 @@
+use crate::api::item_management::add_tag::insert_item_tags;
+use crate::api::item_management::ownership::{check_items_owned, check_tags_owned};
+use crate::api::item_management::remove_tag::delete_item_tags;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(FromForm)]
+pub struct FormBulkTags {
+    items: Vec<i32>,
+    add_tags: Vec<i32>,
+    remove_tags: Vec<i32>,
+}
+
+#[derive(Serialize)]
+pub struct BulkTagResult {
+    pub item_id: i32,
+    /// Tags the item didn't have before.
+    pub added: Vec<i32>,
+    /// Tags the item had before.
+    pub removed: Vec<i32>,
+}
+
+fn dedup(ids: &mut Vec<i32>) {
+    ids.sort_unstable();
+    ids.dedup();
+}
+
+/// Adds `add_tags` to and removes `remove_tags` from all `items` at once. Either every change
+/// happens or, if one of the items or tags isn't the user's, none.
+///
+/// Adding a tag an item already has or removing one it doesn't have does nothing, so the results
+/// only list the actual changes per item.
+#[post("/items/tags", data = "<form_tags>")]
+pub(crate) async fn bulk_tags(
+    form_tags: Form<FormBulkTags>,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<Vec<BulkTagResult>>, ErrorResponse> {
+    let FormBulkTags {
+        mut items,
+        mut add_tags,
+        mut remove_tags,
+    } = form_tags.into_inner();
+    dedup(&mut items);
+    dedup(&mut add_tags);
+    dedup(&mut remove_tags);
+
+    if items.is_empty() || (add_tags.is_empty() && remove_tags.is_empty()) {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Expected at least one item and one tag to add or remove".to_string(),
+        ));
+    }
+    if let Some(tag) = add_tags.iter().find(|tag| remove_tags.contains(tag)) {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            format!("Tag {} can't be added and removed at once", tag),
+        ));
+    }
+
+    let uid = user.0.id;
+    let (items, added, removed) = conn
+        .run(move |c| {
+            c.transaction::<_, ErrorResponse, _>(|| {
+                check_items_owned(c, uid, &items)?;
+                let tag_ids = add_tags
+                    .iter()
+                    .chain(&remove_tags)
+                    .copied()
+                    .collect::<Vec<_>>();
+                check_tags_owned(c, uid, &tag_ids)?;
+
+                let added = insert_item_tags(c, uid, &items, &add_tags)?;
+                let removed = delete_item_tags(c, uid, &items, &remove_tags)?;
+                Ok((items, added, removed))
+            })
+        })
+        .await?;
+
+    let tags_of = |pairs: &[(i32, i32)], iid: i32| {
+        let mut tags = pairs
+            .iter()
+            .filter(|(item, _)| *item == iid)
+            .map(|(_, tag)| *tag)
+            .collect::<Vec<_>>();
+        tags.sort_unstable();
+        tags
+    };
+
+    Ok(Json(
+        items
+            .into_iter()
+            .map(|iid| BulkTagResult {
+                item_id: iid,
+                added: tags_of(&added, iid),
+                removed: tags_of(&removed, iid),
+            })
+            .collect(),
+    ))
+}
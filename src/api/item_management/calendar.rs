@@ -0,0 +1,91 @@
+use crate::api::item_management::attributes::parse_date;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+/// Keeps a single response to about a year of days.
+const MAX_CALENDAR_DAYS: i64 = 366;
+
+#[derive(Serialize)]
+pub struct CalendarItem {
+    pub item_id: i32,
+    pub item_name: String,
+    /// Times the item was used that day.
+    pub uses: i32,
+}
+
+#[derive(Serialize)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub items: Vec<CalendarItem>,
+}
+
+/// Every day from `from` to `to`, both inclusive, with the items used on it.
+#[get("/calendar?<from>&<to>")]
+pub(crate) async fn get_calendar(
+    user: UserLoggedIn,
+    from: String,
+    to: String,
+    conn: DbConn,
+) -> Result<Json<Vec<CalendarDay>>, ErrorResponse> {
+    let from = parse_date(&from)?;
+    let to = parse_date(&to)?;
+    if from > to {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Start of range must not be after its end".to_string(),
+        ));
+    }
+    if (to - from).num_days() >= MAX_CALENDAR_DAYS {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            format!("Range must not exceed {} days", MAX_CALENDAR_DAYS),
+        ));
+    }
+
+    let uid = user.0.id;
+    let used = conn
+        .run(move |c| {
+            use schema::items;
+            use schema::uses;
+
+            uses::table
+                .inner_join(items::table)
+                .filter(items::user_id.eq(uid))
+                .filter(uses::date.between(from, to))
+                .order((uses::date, items::id))
+                .select((uses::date, items::id, items::item_name))
+                .load::<(NaiveDate, i32, String)>(c)
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load uses".to_string()))?;
+
+    let mut used = used.into_iter().peekable();
+    let days = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| {
+            let mut items = Vec::<CalendarItem>::new();
+            while let Some((_, iid, name)) = used.next_if(|(date, _, _)| *date == day) {
+                match items.last_mut() {
+                    Some(last) if last.item_id == iid => last.uses += 1,
+                    _ => items.push(CalendarItem {
+                        item_id: iid,
+                        item_name: name,
+                        uses: 1,
+                    }),
+                }
+            }
+
+            CalendarDay { date: day, items }
+        })
+        .collect();
+
+    Ok(Json(days))
+}
@@ -1,7 +1,8 @@
 use std::path::Path;
 
+use crate::api::item_management::attributes;
 use crate::api::item_management::get_item::ItemOut;
-use crate::api::item_management::models::Item;
+use crate::api::item_management::models::{Item, MovementReason};
 use crate::api::item_management::modify_inventory::NewInventory;
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
@@ -9,6 +10,7 @@ use crate::error::ErrorResponse;
 use crate::schema;
 use crate::schema::items;
 use crate::settings::Settings;
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use rocket::form::Form;
 use rocket::fs::TempFile;
@@ -21,6 +23,14 @@ pub struct FormItem<'a> {
     name: String,
     image: TempFile<'a>,
     count: Option<i32>,
+    brand: Option<String>,
+    size: Option<String>,
+    color: Option<String>,
+    material: Option<String>,
+    purchase_date: Option<String>,
+    purchase_price_cents: Option<String>,
+    purchase_currency: Option<String>,
+    notes: Option<String>,
 }
 
 #[derive(Insertable, AsChangeset)]
@@ -28,6 +38,14 @@ pub struct FormItem<'a> {
 struct NewItem {
     item_name: String,
     user_id: i32,
+    brand: Option<String>,
+    size: Option<String>,
+    color: Option<String>,
+    material: Option<String>,
+    purchase_date: Option<NaiveDate>,
+    purchase_price_cents: Option<i32>,
+    purchase_currency: Option<String>,
+    notes: Option<String>,
 }
 
 #[post("/create_item", data = "<form_item>")]
@@ -42,6 +60,14 @@ pub(crate) async fn create_item(
     let new_item = NewItem {
         item_name: form_item.name.clone(),
         user_id: user.0.id,
+        brand: attributes::text(form_item.brand.take()).flatten(),
+        size: attributes::text(form_item.size.take()).flatten(),
+        color: attributes::text(form_item.color.take()).flatten(),
+        material: attributes::text(form_item.material.take()).flatten(),
+        purchase_date: attributes::date(form_item.purchase_date.take())?.flatten(),
+        purchase_price_cents: attributes::price(form_item.purchase_price_cents.take())?.flatten(),
+        purchase_currency: attributes::currency(form_item.purchase_currency.take())?.flatten(),
+        notes: attributes::text(form_item.notes.take()).flatten(),
     };
     let movement = form_item.count.unwrap_or(1);
 
@@ -55,6 +81,13 @@ pub(crate) async fn create_item(
                 let new_inventory = NewInventory {
                     item_id: item.id,
                     movement,
+                    reason: if movement > 0 {
+                        MovementReason::Purchased.as_str()
+                    } else {
+                        MovementReason::Correction.as_str()
+                    },
+                    note: None,
+                    value_cents: None,
                 };
 
                 diesel::insert_into(schema::item_inventory::dsl::item_inventory)
@@ -92,10 +125,5 @@ pub(crate) async fn create_item(
         ));
     }
 
-    Ok(Json(ItemOut {
-        id: item.id,
-        user_id: item.user_id,
-        item_name: item.item_name,
-        count: 0,
-    }))
+    Ok(Json(ItemOut::from(item).with_counts(0, movement.into())))
 }
@@ -1,22 +1,96 @@
+use crate::api::item_management::attributes;
+use crate::api::item_management::get_tags::{load_tags, TagOut};
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
 use crate::schema;
 use crate::schema::tags;
 use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use rocket::form::Form;
 use rocket::http::Status;
+use rocket::serde::json::Json;
 
 #[derive(Insertable, AsChangeset)]
 #[table_name = "tags"]
 struct NewTag {
     tag_name: String,
     user_id: i32,
+    color: Option<String>,
+    icon: Option<String>,
+    parent_id: Option<i32>,
 }
 
 #[derive(FromForm)]
 pub struct FormTag {
     tag_name: String,
+    color: Option<String>,
+    icon: Option<String>,
+    /// Creates the tag as a subcategory of this one.
+    parent_id: Option<i32>,
+}
+
+pub(crate) fn tag_name(value: String) -> Result<String, ErrorResponse> {
+    attributes::text(Some(value)).flatten().ok_or_else(|| {
+        ErrorResponse::new(
+            Status { code: 400 },
+            "Tag name must not be empty".to_string(),
+        )
+    })
+}
+
+/// Colors are hex codes like `#1e90ff`.
+pub(crate) fn color(value: Option<String>) -> Result<Option<Option<String>>, ErrorResponse> {
+    attributes::text(value)
+        .map(|value| {
+            value
+                .map(|value| {
+                    let hex = value.strip_prefix('#').unwrap_or(&value);
+                    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                        Ok(format!("#{}", hex.to_ascii_lowercase()))
+                    } else {
+                        Err(ErrorResponse::new(
+                            Status { code: 400 },
+                            format!("Invalid color {}, expected a hex code like #1e90ff", value),
+                        ))
+                    }
+                })
+                .transpose()
+        })
+        .transpose()
+}
+
+pub(super) fn check_parent_owned(
+    c: &PgConnection,
+    uid: i32,
+    parent: i32,
+) -> Result<(), ErrorResponse> {
+    use schema::tags::dsl::*;
+
+    tags.filter(user_id.eq(uid).and(id.eq(parent)))
+        .select(id)
+        .first::<i32>(c)
+        .optional()?
+        .map(|_| ())
+        .ok_or_else(|| {
+            ErrorResponse::new(
+                Status { code: 404 },
+                format!("Couldn't load parent tag {}", parent),
+            )
+        })
+}
+
+/// Tag names are unique per user.
+pub(super) fn tag_error(err: DieselError, name: &str) -> ErrorResponse {
+    match err {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            ErrorResponse::new(Status { code: 409 }, format!("Tag {} already exists", name))
+        }
+        DieselError::NotFound => {
+            ErrorResponse::new(Status { code: 404 }, "Couldn't load tag".to_string())
+        }
+        err => ErrorResponse::new(Status { code: 500 }, format!("Couldn't save tag: {}", err)),
+    }
 }
 
 #[post("/tags/create", data = "<form_tag>")]
@@ -24,26 +98,36 @@ pub(crate) async fn create_tag(
     user: UserLoggedIn,
     conn: DbConn,
     form_tag: Form<FormTag>,
-) -> Result<(), ErrorResponse> {
-    use schema::tags::dsl::*;
-
+) -> Result<Json<TagOut>, ErrorResponse> {
+    let form_tag = form_tag.into_inner();
+    let uid = user.0.id;
     let tag = NewTag {
-        tag_name: form_tag.into_inner().tag_name,
-        user_id: user.0.id,
+        tag_name: tag_name(form_tag.tag_name)?,
+        user_id: uid,
+        color: color(form_tag.color)?.flatten(),
+        icon: attributes::text(form_tag.icon).flatten(),
+        parent_id: form_tag.parent_id,
     };
 
     conn.run(move |c| {
-        diesel::insert_into(tags)
-            .values(&tag)
-            .execute(c)
-            .map_err(|err| {
-                ErrorResponse::new(
-                    Status { code: 500 },
-                    format!("Couldn't create tag: {}", err),
-                )
-            })
-    })
-    .await?;
+        if let Some(parent) = tag.parent_id {
+            check_parent_owned(c, uid, parent)?;
+        }
 
-    Ok(())
+        let tid = {
+            use schema::tags::dsl::*;
+            diesel::insert_into(tags)
+                .values(&tag)
+                .returning(id)
+                .get_result::<i32>(c)
+        }
+        .map_err(|err| tag_error(err, &tag.tag_name))?;
+
+        load_tags(c, uid, Some(&[tid]))
+            .map_err(|err| tag_error(err, &tag.tag_name))?
+            .pop()
+            .map(Json)
+            .ok_or_else(|| tag_error(DieselError::NotFound, &tag.tag_name))
+    })
+    .await
 }
@@ -47,6 +47,10 @@ pub(crate) async fn delete_item(
                     use schema::item_inventory::dsl::*;
                     diesel::delete(item_inventory.filter(item_id.eq(cid))).execute(c)
                 }?;
+                {
+                    use schema::outfit_items::dsl::*;
+                    diesel::delete(outfit_items.filter(item_id.eq(cid))).execute(c)
+                }?;
                 {
                     use schema::items::dsl::*;
                     diesel::delete(items.filter(id.eq(iid))).execute(c)
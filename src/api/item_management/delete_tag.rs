@@ -1,3 +1,4 @@
+use crate::api::smart_tag_management::references::check_unreferenced;
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
@@ -5,6 +6,7 @@ use crate::schema;
 use diesel::prelude::*;
 use rocket::http::Status;
 
+/// Tags that smart tags refer to can't be deleted.
 #[delete("/tag/<tid>")]
 pub(crate) async fn delete_tag(
     user: UserLoggedIn,
@@ -14,13 +16,25 @@ pub(crate) async fn delete_tag(
     conn.run(move |c| {
         c.build_transaction()
             .read_write()
-            .run::<_, diesel::result::Error, _>(|| {
-                {
+            .run::<_, ErrorResponse, _>(|| {
+                let (name, parent) = {
                     // Tag owned
                     use schema::tags::dsl::*;
                     tags.filter(id.eq(tid).and(user_id.eq(user.0.id)))
-                        .select(id)
-                        .first::<i32>(c)
+                        .select((tag_name, parent_id))
+                        .first::<(String, Option<i32>)>(c)
+                        .optional()
+                }?
+                .ok_or_else(|| {
+                    ErrorResponse::new(Status { code: 404 }, "Couldn't load tag".to_string())
+                })?;
+                check_unreferenced(c, user.0.id, &name)?;
+                {
+                    // Subcategories move up a level instead of disappearing
+                    use schema::tags::dsl::*;
+                    diesel::update(tags.filter(parent_id.eq(tid)))
+                        .set(parent_id.eq(parent))
+                        .execute(c)
                 }?;
                 {
                     use schema::item_tags::dsl::*;
@@ -35,12 +49,4 @@ pub(crate) async fn delete_tag(
             })
     })
     .await
-    .map_err(|_| {
-        ErrorResponse::new(
-            Status { code: 500 },
-            "Couldn't delete database entries".to_string(),
-        )
-    })?;
-
-    Ok(())
 }
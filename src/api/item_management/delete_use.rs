@@ -0,0 +1,61 @@
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+
+/// A use logged by wearing an outfit takes the whole wear with it, so the outfit's history and
+/// the uses of its other items stay in agreement.
+#[delete("/use/<use_id>")]
+pub(crate) async fn delete_use(
+    use_id: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<(), ErrorResponse> {
+    let uid = user.0.id;
+    let deleted = conn
+        .run(move |c| {
+            c.transaction::<_, diesel::result::Error, _>(|| {
+                use schema::uses::dsl::*;
+
+                let owned_items = schema::items::table
+                    .filter(schema::items::user_id.eq(uid))
+                    .select(schema::items::id);
+
+                let wear = uses
+                    .filter(id.eq(use_id).and(item_id.eq_any(owned_items)))
+                    .select(outfit_wear_id)
+                    .for_update()
+                    .first::<Option<i32>>(c)
+                    .optional()?;
+
+                match wear {
+                    None => Ok(false),
+                    Some(None) => {
+                        diesel::delete(uses.filter(id.eq(use_id))).execute(c)?;
+                        Ok(true)
+                    }
+                    Some(Some(wid)) => {
+                        diesel::delete(uses.filter(outfit_wear_id.eq(wid))).execute(c)?;
+                        diesel::delete(
+                            schema::outfit_wears::table.filter(schema::outfit_wears::id.eq(wid)),
+                        )
+                        .execute(c)?;
+                        Ok(true)
+                    }
+                }
+            })
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't delete use".to_string()))?;
+
+    if !deleted {
+        return Err(ErrorResponse::new(
+            Status { code: 404 },
+            "Couldn't find use".to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use std::path::Path;
 
+use crate::api::item_management::attributes;
 use crate::api::item_management::get_item::ItemOut;
 use crate::api::item_management::models::Item;
+use crate::api::item_management::queries;
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
@@ -18,6 +20,21 @@ use rocket::State;
 pub struct FormEditItem<'a> {
     name: Option<String>,
     image: Option<TempFile<'a>>,
+    brand: Option<String>,
+    size: Option<String>,
+    color: Option<String>,
+    material: Option<String>,
+    purchase_date: Option<String>,
+    purchase_price_cents: Option<String>,
+    purchase_currency: Option<String>,
+    notes: Option<String>,
+}
+
+/// Leaves `target` alone for fields that weren't sent.
+pub(super) fn update<T>(target: &mut Option<T>, value: Option<Option<T>>) {
+    if let Some(value) = value {
+        *target = value;
+    }
 }
 
 #[post("/item/<item_id>/edit", data = "<form_item>")]
@@ -38,21 +55,48 @@ pub(crate) async fn edit_item(
         })
         .await
         .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't get item".to_string()))?;
-    let uid = item.user_id;
-    let final_name = form_item.name.as_ref().unwrap_or(&item.item_name).clone();
 
-    if let Some(name) = &form_item.name {
-        item.item_name = name.clone();
+    if let Some(name) = form_item.name.take() {
+        item.item_name = name;
+    }
+    update(&mut item.brand, attributes::text(form_item.brand.take()));
+    update(&mut item.size, attributes::text(form_item.size.take()));
+    update(&mut item.color, attributes::text(form_item.color.take()));
+    update(
+        &mut item.material,
+        attributes::text(form_item.material.take()),
+    );
+    update(
+        &mut item.purchase_date,
+        attributes::date(form_item.purchase_date.take())?,
+    );
+    update(
+        &mut item.purchase_price_cents,
+        attributes::price(form_item.purchase_price_cents.take())?,
+    );
+    update(
+        &mut item.purchase_currency,
+        attributes::currency(form_item.purchase_currency.take())?,
+    );
+    update(&mut item.notes, attributes::text(form_item.notes.take()));
+
+    let (item, (use_count, inventory_count)) = conn
+        .run(move |c| {
+            let item = item.save_changes::<Item>(c)?;
+            let counts = (
+                queries::use_count(c, item.id)?,
+                queries::inventory_count(c, item.id)?,
+            );
 
-        conn.run(move |c| item.save_changes::<Item>(c))
-            .await
-            .map_err(|err| {
-                ErrorResponse::new(
-                    Status { code: 500 },
-                    format!("Couldn't update data: {}", err),
-                )
-            })?;
-    };
+            Ok::<_, diesel::result::Error>((item, counts))
+        })
+        .await
+        .map_err(|err| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                format!("Couldn't update data: {}", err),
+            )
+        })?;
 
     if let Some(file) = &mut form_item.image {
         let image_file = Path::new(&settings.image_folder).join(item_id.to_string());
@@ -64,10 +108,7 @@ pub(crate) async fn edit_item(
         })?;
     }
 
-    Ok(Json(ItemOut {
-        id: item_id,
-        user_id: uid,
-        item_name: final_name,
-        count: 0,
-    }))
+    Ok(Json(
+        ItemOut::from(item).with_counts(use_count as i32, inventory_count),
+    ))
 }
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use crate::api::item_management::attributes;
+use crate::api::item_management::create_tag::{color, tag_error, tag_name};
+use crate::api::item_management::edit::update;
+use crate::api::item_management::get_tags::{load_tags, TagOut};
+use crate::api::item_management::models::Tag;
+use crate::api::smart_tag_management::references::rename_tag_references;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use rocket::form::Form;
+use rocket::serde::json::Json;
+
+/// Fields that aren't sent stay as they are, empty ones clear the color or icon.
+#[derive(FromForm)]
+pub struct FormEditTag {
+    tag_name: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+}
+
+/// Smart tags referring to the tag by its old name are changed to use the new one.
+#[post("/tag/<tid>/edit", data = "<form_tag>")]
+pub(crate) async fn edit_tag(
+    form_tag: Form<FormEditTag>,
+    tid: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<TagOut>, ErrorResponse> {
+    let form_tag = form_tag.into_inner();
+    let new_name = form_tag.tag_name.map(tag_name).transpose()?;
+    let new_color = color(form_tag.color)?;
+    let new_icon = attributes::text(form_tag.icon);
+    let uid = user.0.id;
+
+    conn.run(move |c| {
+        c.transaction::<_, ErrorResponse, _>(|| {
+            let mut tag = {
+                use schema::tags::dsl::*;
+                tags.filter(user_id.eq(uid).and(id.eq(tid))).first::<Tag>(c)
+            }
+            .map_err(|err| tag_error(err, ""))?;
+
+            let old_name = tag.tag_name.clone();
+            if let Some(new_name) = new_name {
+                tag.tag_name = new_name;
+            }
+            update(&mut tag.color, new_color);
+            update(&mut tag.icon, new_icon);
+
+            let tag = tag
+                .save_changes::<Tag>(c)
+                .map_err(|err| tag_error(err, &tag.tag_name))?;
+            if tag.tag_name != old_name {
+                rename_tag_references(c, uid, &HashMap::from([(old_name, tag.tag_name.clone())]))?;
+            }
+
+            load_tags(c, uid, Some(&[tag.id]))
+                .map_err(|err| tag_error(err, &tag.tag_name))?
+                .pop()
+                .map(Json)
+                .ok_or_else(|| tag_error(DieselError::NotFound, &tag.tag_name))
+        })
+    })
+    .await
+}
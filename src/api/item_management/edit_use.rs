@@ -0,0 +1,70 @@
+use crate::api::item_management::attributes::use_date;
+use crate::api::item_management::get_uses::UseOut;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[derive(FromForm)]
+pub struct FormEditUse {
+    date: String,
+}
+
+/// A use logged by wearing an outfit moves together with the whole wear, like `delete_use`.
+#[patch("/use/<use_id>", data = "<form_use>")]
+pub(crate) async fn edit_use(
+    use_id: i32,
+    form_use: Form<FormEditUse>,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<UseOut>, ErrorResponse> {
+    let new_date = use_date(&form_use.date)?;
+
+    let uid = user.0.id;
+    let updated = conn
+        .run(move |c| {
+            c.transaction::<_, diesel::result::Error, _>(|| {
+                use schema::uses::dsl::*;
+
+                let owned_items = schema::items::table
+                    .filter(schema::items::user_id.eq(uid))
+                    .select(schema::items::id);
+
+                let wear = uses
+                    .filter(id.eq(use_id).and(item_id.eq_any(owned_items)))
+                    .select(outfit_wear_id)
+                    .for_update()
+                    .first::<Option<i32>>(c)
+                    .optional()?;
+
+                match wear {
+                    None => return Ok(None),
+                    Some(None) => {}
+                    Some(Some(wid)) => {
+                        diesel::update(uses.filter(outfit_wear_id.eq(wid)))
+                            .set(date.eq(new_date))
+                            .execute(c)?;
+                        diesel::update(
+                            schema::outfit_wears::table.filter(schema::outfit_wears::id.eq(wid)),
+                        )
+                        .set(schema::outfit_wears::date.eq(new_date))
+                        .execute(c)?;
+                    }
+                }
+
+                diesel::update(uses.filter(id.eq(use_id)))
+                    .set(date.eq(new_date))
+                    .get_result::<UseOut>(c)
+                    .map(Some)
+            })
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't update use".to_string()))?
+        .ok_or_else(|| ErrorResponse::new(Status { code: 404 }, "Couldn't find use".to_string()))?;
+
+    Ok(Json(updated))
+}
@@ -0,0 +1,60 @@
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Queryable, Serialize)]
+pub struct InventoryOut {
+    pub id: i32,
+    pub movement: i32,
+    pub update_time: NaiveDateTime,
+    pub reason: String,
+    pub note: Option<String>,
+    pub value_cents: Option<i32>,
+}
+
+/// Inventory movements of an item, oldest first.
+#[get("/item/<item>/inventory")]
+pub(crate) async fn get_inventory(
+    user: UserLoggedIn,
+    item: i32,
+    conn: DbConn,
+) -> Result<Json<Vec<InventoryOut>>, ErrorResponse> {
+    let uid = user.0.id;
+    let movements = conn
+        .run(move |c| {
+            let owned = {
+                use schema::items::dsl::*;
+                items
+                    .filter(user_id.eq(uid).and(id.eq(item)))
+                    .select(id)
+                    .first::<i32>(c)
+                    .optional()
+            }?;
+            if owned.is_none() {
+                return Ok(None);
+            }
+
+            use schema::item_inventory::dsl::*;
+            item_inventory
+                .filter(item_id.eq(item))
+                .order((update_time, id))
+                .select((id, movement, update_time, reason, note, value_cents))
+                .load::<InventoryOut>(c)
+                .map(Some)
+        })
+        .await
+        .map_err(|_: diesel::result::Error| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load inventory".to_string())
+        })?
+        .ok_or_else(|| {
+            ErrorResponse::new(Status { code: 404 }, "Couldn't load item".to_string())
+        })?;
+
+    Ok(Json(movements))
+}
@@ -2,11 +2,13 @@ use std::fs::File;
 use std::path::Path;
 
 use crate::api::item_management::models::Item;
+use crate::api::item_management::queries;
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
 use crate::schema;
 use crate::settings::Settings;
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use rocket::http::Status;
 use rocket::serde::json::Json;
@@ -18,7 +20,60 @@ pub struct ItemOut {
     pub id: i32,
     pub user_id: i32,
     pub item_name: String,
-    pub count: i32,
+    pub use_count: i32,
+    /// Sum of all inventory movements, i.e. how many of the item are owned.
+    pub inventory_count: i64,
+    pub brand: Option<String>,
+    pub size: Option<String>,
+    pub color: Option<String>,
+    pub material: Option<String>,
+    pub purchase_date: Option<NaiveDate>,
+    pub purchase_price_cents: Option<i32>,
+    pub purchase_currency: Option<String>,
+    pub notes: Option<String>,
+    /// Purchase price in cents of `purchase_currency` divided by `use_count`.
+    pub cost_per_wear_cents: Option<f64>,
+}
+
+/// Spreads the purchase price over the wears so far. Until an item is worn for the first time, it
+/// costs its full price per wear.
+pub(crate) fn cost_per_wear(price_cents: i64, uses: i64) -> f64 {
+    price_cents as f64 / uses.max(1) as f64
+}
+
+impl ItemOut {
+    pub(crate) fn with_counts(self, use_count: i32, inventory_count: i64) -> Self {
+        ItemOut {
+            use_count,
+            inventory_count,
+            cost_per_wear_cents: self
+                .purchase_price_cents
+                .map(|price| cost_per_wear(price.into(), use_count.into())),
+            ..self
+        }
+    }
+}
+
+impl From<Item> for ItemOut {
+    fn from(item: Item) -> Self {
+        ItemOut {
+            id: item.id,
+            user_id: item.user_id,
+            item_name: item.item_name,
+            use_count: 0,
+            inventory_count: 0,
+            brand: item.brand,
+            size: item.size,
+            color: item.color,
+            material: item.material,
+            purchase_date: item.purchase_date,
+            purchase_price_cents: item.purchase_price_cents,
+            purchase_currency: item.purchase_currency,
+            notes: item.notes,
+            cost_per_wear_cents: None,
+        }
+        .with_counts(0, 0)
+    }
 }
 
 #[get("/item/<item>")]
@@ -41,32 +96,24 @@ pub(crate) async fn get_item(
         .await?;
 
     let item = item_list
-        .first()
-        .map(|first| ItemOut {
-            id: first.id,
-            user_id: first.user_id,
-            item_name: first.item_name.clone(),
-            count: 0,
-        })
+        .into_iter()
+        .next()
+        .map(ItemOut::from)
         .ok_or_else(|| {
             ErrorResponse::new(Status { code: 404 }, "Couldn't load item".to_string())
         })?;
 
-    let count = conn
+    let (use_count, inventory_count) = conn
         .run(move |c| {
-            use schema::uses::dsl::*;
-
-            uses.filter(item_id.eq(item.id))
-                .count()
-                .get_result(c)
-                .map(|val: i64| val as i32)
-                .map_err(|_| {
-                    ErrorResponse::new(Status { code: 404 }, "Couldn't get use count".to_string())
-                })
+            Ok::<_, diesel::result::Error>((
+                queries::use_count(c, item.id)?,
+                queries::inventory_count(c, item.id)?,
+            ))
         })
-        .await?;
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 404 }, "Couldn't get counts".to_string()))?;
 
-    Ok(Json(ItemOut { count, ..item }))
+    Ok(Json(item.with_counts(use_count as i32, inventory_count)))
 }
 
 #[get("/item/<item>/image")]
@@ -1,3 +1,4 @@
+use crate::api::item_management::get_tags::{load_tags, TagOut};
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
@@ -11,25 +12,24 @@ pub(crate) async fn get_item_tags(
     user: UserLoggedIn,
     item: i32,
     conn: DbConn,
-) -> Result<Json<Vec<String>>, ErrorResponse> {
+) -> Result<Json<Vec<TagOut>>, ErrorResponse> {
     use schema::item_tags;
     use schema::items;
-    use schema::tags;
-    use schema::users;
 
+    let uid = user.0.id;
     let out = conn
         .run(move |c| {
-            users::table
-                .filter(users::id.eq(user.0.id))
-                .inner_join(items::table.on(items::user_id.eq(users::id)))
+            let tag_ids = items::table
+                .filter(items::user_id.eq(uid))
                 .filter(items::id.eq(item))
                 .inner_join(item_tags::table.on(item_tags::item_id.eq(items::id)))
-                .inner_join(tags::table.on(tags::id.eq(item_tags::item_id)))
-                .select(tags::tag_name)
-                .load::<String>(c)
+                .select(item_tags::tag_id)
+                .load::<i32>(c)?;
+
+            load_tags(c, uid, Some(&tag_ids))
         })
         .await
-        .map_err(|_| ErrorResponse::new(Status { code: 500 }, format!("Couldn't get tags")))?;
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't get tags".to_string()))?;
 
     Ok(Json(out))
 }
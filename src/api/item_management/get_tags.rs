@@ -1,3 +1,7 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::api::item_management::models::Tag;
+use crate::api::item_management::tag_tree::TagTree;
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
@@ -5,25 +9,100 @@ use crate::schema;
 use diesel::prelude::*;
 use rocket::http::Status;
 use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct TagOut {
+    pub id: i32,
+    pub tag_name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub parent_id: Option<i32>,
+    /// Number of items carrying the tag.
+    pub usage_count: i64,
+    /// Number of items carrying the tag or any tag below it.
+    pub total_usage_count: i64,
+}
+
+/// Loads the user's tags, or only those in `tag_ids` if given, sorted by name.
+pub(crate) fn load_tags(
+    c: &PgConnection,
+    uid: i32,
+    tag_ids: Option<&[i32]>,
+) -> QueryResult<Vec<TagOut>> {
+    let tag_list = {
+        use schema::tags::dsl::*;
+        let mut query = tags
+            .filter(user_id.eq(uid))
+            .order((tag_name, id))
+            .into_boxed();
+        if let Some(tag_ids) = tag_ids {
+            query = query.filter(id.eq_any(tag_ids));
+        }
+        query.load::<Tag>(c)
+    }?;
+    let tree = TagTree::load(c, uid)?;
+    let subtrees = tag_list
+        .iter()
+        .map(|tag| (tag.id, tree.subtree(tag.id)))
+        .collect::<HashMap<_, _>>();
+
+    let tagged = {
+        use schema::item_tags;
+        use schema::tags;
+        let mut query = item_tags::table
+            .inner_join(tags::table)
+            .filter(tags::user_id.eq(uid))
+            .select((item_tags::tag_id, item_tags::item_id))
+            .into_boxed();
+        if tag_ids.is_some() {
+            let relevant = subtrees
+                .values()
+                .flatten()
+                .copied()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            query = query.filter(item_tags::tag_id.eq_any(relevant));
+        }
+        query.load::<(i32, i32)>(c)
+    }?;
+    let mut items_of = HashMap::<i32, Vec<i32>>::new();
+    for (tid, iid) in tagged {
+        items_of.entry(tid).or_default().push(iid);
+    }
+
+    Ok(tag_list
+        .into_iter()
+        .map(|tag| {
+            let total_usage_count = subtrees[&tag.id]
+                .iter()
+                .filter_map(|tid| items_of.get(tid))
+                .flatten()
+                .collect::<HashSet<_>>()
+                .len() as i64;
+            TagOut {
+                usage_count: items_of.get(&tag.id).map_or(0, Vec::len) as i64,
+                total_usage_count,
+                id: tag.id,
+                tag_name: tag.tag_name,
+                color: tag.color,
+                icon: tag.icon,
+                parent_id: tag.parent_id,
+            }
+        })
+        .collect())
+}
 
 #[get("/tags")]
 pub(crate) async fn get_tags(
     user: UserLoggedIn,
     conn: DbConn,
-) -> Result<Json<Vec<String>>, ErrorResponse> {
-    use schema::tags;
-    use schema::users;
-
+) -> Result<Json<Vec<TagOut>>, ErrorResponse> {
     let out = conn
-        .run(move |c| {
-            users::table
-                .filter(users::id.eq(user.0.id))
-                .inner_join(tags::table.on(tags::user_id.eq(users::id)))
-                .select(tags::tag_name)
-                .load::<String>(c)
-        })
+        .run(move |c| load_tags(c, user.0.id, None))
         .await
-        .map_err(|_| ErrorResponse::new(Status { code: 500 }, format!("Couldn't get tags")))?;
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't get tags".to_string()))?;
 
     Ok(Json(out))
 }
@@ -0,0 +1,68 @@
+use crate::api::item_management::attributes::parse_date;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Queryable, Serialize)]
+pub struct UseOut {
+    pub id: i32,
+    pub item_id: i32,
+    pub date: NaiveDate,
+    /// Set when the use was logged by wearing an outfit.
+    pub outfit_wear_id: Option<i32>,
+}
+
+/// Uses of an item, oldest first. `from` and `to` are inclusive.
+#[get("/item/<item>/uses?<from>&<to>")]
+pub(crate) async fn get_uses(
+    user: UserLoggedIn,
+    item: i32,
+    from: Option<String>,
+    to: Option<String>,
+    conn: DbConn,
+) -> Result<Json<Vec<UseOut>>, ErrorResponse> {
+    let from = from.map(|from| parse_date(&from)).transpose()?;
+    let to = to.map(|to| parse_date(&to)).transpose()?;
+
+    let uid = user.0.id;
+    let item_uses = conn
+        .run(move |c| {
+            let owned = {
+                use schema::items::dsl::*;
+                items
+                    .filter(user_id.eq(uid).and(id.eq(item)))
+                    .select(id)
+                    .first::<i32>(c)
+                    .optional()
+            }?;
+            if owned.is_none() {
+                return Ok(None);
+            }
+
+            use schema::uses::dsl::*;
+            let mut query = uses.filter(item_id.eq(item)).into_boxed();
+            if let Some(from) = from {
+                query = query.filter(date.ge(from));
+            }
+            if let Some(to) = to {
+                query = query.filter(date.le(to));
+            }
+
+            query.order((date, id)).load::<UseOut>(c).map(Some)
+        })
+        .await
+        .map_err(|_: diesel::result::Error| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load uses".to_string())
+        })?
+        .ok_or_else(|| {
+            ErrorResponse::new(Status { code: 404 }, "Couldn't load item".to_string())
+        })?;
+
+    Ok(Json(item_uses))
+}
@@ -1,16 +1,25 @@
 use crate::api::item_management::get_item::ItemOut;
 use crate::api::item_management::models::Item;
+use crate::api::item_management::queries::inventory_counts;
+use crate::api::item_management::tag_tree::filter_tags;
+use crate::api::smart_tag_management::filter::{item_filter, FilterContext};
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
 use crate::schema;
+use chrono::Local;
 use diesel::prelude::*;
 use rocket::http::Status;
 use rocket::serde::json::Json;
 
-#[get("/items")]
+/// `tag` only lists items with that tag, or with `descendants` also those with a tag below it.
+/// `smart_tag` only lists the items the smart tag selects.
+#[get("/items?<tag>&<descendants>&<smart_tag>")]
 pub(crate) async fn get_items(
     user: UserLoggedIn,
+    tag: Option<i32>,
+    descendants: bool,
+    smart_tag: Option<i32>,
     conn: DbConn,
 ) -> Result<Json<Vec<ItemOut>>, ErrorResponse> {
     use schema::items::dsl::*;
@@ -41,12 +50,38 @@ pub(crate) async fn get_items(
 
     let item_list = conn
         .run(move |c| {
-            items
-                .filter(user_id.eq(user.0.id))
-                .load::<Item>(c)
-                .map_err(|_| {
-                    ErrorResponse::new(Status { code: 500 }, "Couldn't load items".to_string())
-                })
+            let mut query = items.filter(user_id.eq(user.0.id)).into_boxed();
+            if let Some(tag) = tag {
+                use schema::item_tags;
+                let tag_ids = filter_tags(c, user.0.id, tag, descendants)?;
+                query = query.filter(
+                    schema::items::id.eq_any(
+                        item_tags::table
+                            .filter(item_tags::tag_id.eq_any(tag_ids))
+                            .select(item_tags::item_id),
+                    ),
+                );
+            }
+            if let Some(smart_tag) = smart_tag {
+                use schema::smart_tags;
+                let smart_tag_expression = smart_tags::table
+                    .filter(smart_tags::user_id.eq(user.0.id))
+                    .filter(smart_tags::id.eq(smart_tag))
+                    .select(smart_tags::expression)
+                    .first::<String>(c)
+                    .optional()?
+                    .ok_or_else(|| {
+                        ErrorResponse::new(
+                            Status { code: 404 },
+                            "Couldn't load smart tag".to_string(),
+                        )
+                    })?;
+                let context = FilterContext::load(c, user.0.id, Local::now().date_naive())?;
+                query = query.filter(item_filter(&smart_tag_expression, &context));
+            }
+            query.load::<Item>(c).map_err(|_| {
+                ErrorResponse::new(Status { code: 500 }, "Couldn't load items".to_string())
+            })
         })
         .await?;
 
@@ -54,6 +89,14 @@ pub(crate) async fn get_items(
 
     let out = conn
         .run(|c| {
+            let item_ids = item_list.iter().map(|item| item.id).collect::<Vec<_>>();
+            let inventory = inventory_counts(c, &item_ids).map_err(|_| {
+                ErrorResponse::new(
+                    Status { code: 500 },
+                    "Couldn't get inventory counts".to_string(),
+                )
+            })?;
+
             item_list
                 .into_iter()
                 .map(|item| {
@@ -69,14 +112,10 @@ pub(crate) async fn get_items(
                             )
                         })?;
 
-                    Ok(ItemOut {
-                        id: item.id,
-                        user_id: item.user_id,
-                        item_name: item.item_name,
-                        count,
-                    })
+                    let inventory_count = inventory.get(&item.id).copied().unwrap_or(0);
+                    Ok(ItemOut::from(item).with_counts(count, inventory_count))
                 })
-                .collect::<Result<Vec<_>, _>>()
+                .collect::<Result<Vec<_>, ErrorResponse>>()
         })
         .await?;
 
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::api::item_management::add_tag::insert_item_tags;
+use crate::api::item_management::get_item::ItemOut;
+use crate::api::item_management::models::Item;
+use crate::api::item_management::ownership::check_items_owned;
+use crate::api::item_management::queries;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use crate::settings::Settings;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+
+#[derive(FromForm)]
+pub struct FormMergeItems {
+    items: Vec<i32>,
+    /// The item whose image to keep.
+    image: Option<i32>,
+}
+
+/// Attributes the survivor lacks are taken from the first merged item that has them.
+fn fill<T: Clone>(target: &mut Option<T>, merged: &[Item], value: impl Fn(&Item) -> &Option<T>) {
+    if target.is_none() {
+        *target = merged.iter().find_map(|item| value(item).clone());
+    }
+}
+
+/// Uses `(id, item_id, outfit_wear_id)` of the survivor `iid` and the merged items that would
+/// log the same outfit wear twice once they all belong to `iid`. Wearing an outfit logs one use
+/// per item, so of the uses of a wear only the survivor's, or else the first merged one, is kept.
+fn duplicate_wear_uses(iid: i32, worn: &[(i32, i32, Option<i32>)]) -> Vec<i32> {
+    let mut seen = worn
+        .iter()
+        .filter(|(_, item, _)| *item == iid)
+        .filter_map(|(_, _, wear)| *wear)
+        .collect::<HashSet<_>>();
+    worn.iter()
+        .filter(|(_, item, wear)| *item != iid && wear.is_some_and(|wear| !seen.insert(wear)))
+        .map(|(use_id, _, _)| *use_id)
+        .collect()
+}
+
+/// Merges `items` into the item `iid`, which keeps its name and attributes. Uses, inventory
+/// movements, tags and outfits of the merged items all move to `iid`.
+///
+/// `image` picks which of the items' images to keep. By default, `iid` keeps its own image or,
+/// if it has none, takes the one of the first merged item with an image.
+#[post("/item/<iid>/merge", data = "<form_items>")]
+pub(crate) async fn merge_items(
+    form_items: Form<FormMergeItems>,
+    iid: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+    settings: &State<Settings>,
+) -> Result<Json<ItemOut>, ErrorResponse> {
+    let FormMergeItems {
+        items: mut merged,
+        image,
+    } = form_items.into_inner();
+    let mut seen = HashSet::from([iid]);
+    merged.retain(|mid| seen.insert(*mid));
+    if merged.is_empty() {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Expected items other than the one they are merged into".to_string(),
+        ));
+    }
+
+    let image_folder = Path::new(&settings.image_folder).to_path_buf();
+    let image_of = move |item: i32| image_folder.join(item.to_string());
+
+    let uid = user.0.id;
+    let (item, (use_count, inventory_count)) = conn
+        .run(move |c| {
+            let (item, image_source) = c.transaction::<_, ErrorResponse, _>(|| {
+                // Only look at images once the items are known to be the user's, or the errors
+                // would tell whether other users' items have one
+                check_items_owned(c, uid, &[iid])?;
+                check_items_owned(c, uid, &merged)?;
+                let image_source = match image {
+                    Some(image) if image != iid && !merged.contains(&image) => {
+                        return Err(ErrorResponse::new(
+                            Status { code: 400 },
+                            format!("Item {} isn't part of the merge", image),
+                        ))
+                    }
+                    Some(image) if !image_of(image).is_file() => {
+                        return Err(ErrorResponse::new(
+                            Status { code: 400 },
+                            format!("Item {} has no image", image),
+                        ))
+                    }
+                    Some(image) => Some(image),
+                    None => std::iter::once(iid)
+                        .chain(merged.iter().copied())
+                        .find(|item| image_of(*item).is_file()),
+                };
+
+                let mut item = {
+                    use schema::items::dsl::*;
+                    items.filter(id.eq(iid)).for_update().first::<Item>(c)
+                }?;
+                let merged_items = {
+                    use schema::items::dsl::*;
+                    items.filter(id.eq_any(&merged)).load::<Item>(c)
+                }?;
+                let merged_items = merged
+                    .iter()
+                    .filter_map(|mid| merged_items.iter().find(|item| item.id == *mid))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                {
+                    use schema::uses::dsl::*;
+                    let worn = uses
+                        .filter(item_id.eq(iid).or(item_id.eq_any(&merged)))
+                        .filter(outfit_wear_id.is_not_null())
+                        .order(id)
+                        .select((id, item_id, outfit_wear_id))
+                        .load::<(i32, i32, Option<i32>)>(c)?;
+                    diesel::delete(uses.filter(id.eq_any(duplicate_wear_uses(iid, &worn))))
+                        .execute(c)?;
+                    diesel::update(uses.filter(item_id.eq_any(&merged)))
+                        .set(item_id.eq(iid))
+                        .execute(c)
+                }?;
+                {
+                    use schema::item_inventory::dsl::*;
+                    diesel::update(item_inventory.filter(item_id.eq_any(&merged)))
+                        .set(item_id.eq(iid))
+                        .execute(c)
+                }?;
+                {
+                    use schema::item_tags::dsl::*;
+                    let merged_tags = item_tags
+                        .filter(item_id.eq_any(&merged))
+                        .select(tag_id)
+                        .load::<i32>(c)?;
+                    insert_item_tags(c, uid, &[iid], &merged_tags)?;
+                    diesel::delete(item_tags.filter(item_id.eq_any(&merged))).execute(c)
+                }?;
+                {
+                    use schema::outfit_items::dsl::*;
+                    let merged_outfits = outfit_items
+                        .filter(item_id.eq_any(&merged))
+                        .select(outfit_id)
+                        .load::<i32>(c)?;
+                    let rows = merged_outfits
+                        .into_iter()
+                        .map(|oid| (outfit_id.eq(oid), item_id.eq(iid)))
+                        .collect::<Vec<_>>();
+                    diesel::insert_into(outfit_items)
+                        .values(&rows)
+                        .on_conflict_do_nothing()
+                        .execute(c)?;
+                    diesel::delete(outfit_items.filter(item_id.eq_any(&merged))).execute(c)
+                }?;
+
+                fill(&mut item.brand, &merged_items, |item| &item.brand);
+                fill(&mut item.size, &merged_items, |item| &item.size);
+                fill(&mut item.color, &merged_items, |item| &item.color);
+                fill(&mut item.material, &merged_items, |item| &item.material);
+                fill(&mut item.purchase_date, &merged_items, |item| {
+                    &item.purchase_date
+                });
+                // Price and currency only make sense together
+                if item.purchase_price_cents.is_none() {
+                    if let Some(priced) = merged_items
+                        .iter()
+                        .find(|item| item.purchase_price_cents.is_some())
+                    {
+                        item.purchase_price_cents = priced.purchase_price_cents;
+                        item.purchase_currency = priced.purchase_currency.clone();
+                    }
+                }
+                fill(&mut item.notes, &merged_items, |item| &item.notes);
+                let item = item.save_changes::<Item>(c)?;
+
+                {
+                    use schema::items::dsl::*;
+                    diesel::delete(items.filter(id.eq_any(&merged))).execute(c)
+                }?;
+
+                Ok((item, image_source))
+            })?;
+
+            // Only touch the images once the merge is committed. The merge can't be undone by
+            // then, so failing to keep the image only leaves it where it was
+            let mut unmoved = None;
+            if let Some(source) = image_source.filter(|source| *source != iid) {
+                if let Err(err) = fs::rename(image_of(source), image_of(iid)) {
+                    error!(
+                        "Couldn't keep image of item {} for merged item {}: {}",
+                        source, iid, err
+                    );
+                    unmoved = Some(source);
+                }
+            }
+            for mid in merged.iter().filter(|mid| Some(**mid) != unmoved) {
+                fs::remove_file(image_of(*mid)).ok();
+            }
+
+            let counts = (
+                queries::use_count(c, item.id)?,
+                queries::inventory_count(c, item.id)?,
+            );
+            Ok::<_, ErrorResponse>((item, counts))
+        })
+        .await?;
+
+    Ok(Json(
+        ItemOut::from(item).with_counts(use_count as i32, inventory_count),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_one_use_per_outfit_wear() {
+        let worn = [
+            // The survivor 1 and the merged item 2 were worn together in wear 10
+            (100, 1, Some(10)),
+            (101, 2, Some(10)),
+            // Merged items 2 and 3 were worn together in wear 11
+            (102, 2, Some(11)),
+            (103, 3, Some(11)),
+            // Only the merged item 3 was worn in wear 12
+            (104, 3, Some(12)),
+            (105, 2, None),
+        ];
+        assert_eq!(duplicate_wear_uses(1, &worn), vec![101, 103]);
+        assert_eq!(duplicate_wear_uses(1, &[]), Vec::<i32>::new());
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::api::item_management::add_tag::insert_item_tags;
+use crate::api::item_management::get_tags::{load_tags, TagOut};
+use crate::api::item_management::ownership::check_tags_owned;
+use crate::api::item_management::tag_tree::TagTree;
+use crate::api::smart_tag_management::references::rename_tag_references;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[derive(FromForm)]
+pub struct FormMergeTags {
+    tags: Vec<i32>,
+}
+
+/// Merges `tags` into the tag `tid`, which keeps its name, color and icon. Items carrying any of
+/// the merged tags carry `tid` instead, and tags below a merged tag move below `tid`. Smart tags
+/// referring to a merged tag refer to `tid` instead.
+#[post("/tag/<tid>/merge", data = "<form_tags>")]
+pub(crate) async fn merge_tags(
+    form_tags: Form<FormMergeTags>,
+    tid: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<TagOut>, ErrorResponse> {
+    let mut merged = form_tags.into_inner().tags;
+    merged.sort_unstable();
+    merged.dedup();
+    if merged.is_empty() || merged.contains(&tid) {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Expected tags other than the one they are merged into".to_string(),
+        ));
+    }
+
+    let uid = user.0.id;
+    conn.run(move |c| {
+        c.transaction::<_, ErrorResponse, _>(|| {
+            check_tags_owned(c, uid, &[tid])?;
+            check_tags_owned(c, uid, &merged)?;
+            // Locked like when moving tags, as the hierarchy changes too
+            let tree = {
+                use schema::tags::dsl::*;
+                tags.filter(user_id.eq(uid))
+                    .select((id, parent_id))
+                    .for_update()
+                    .load::<(i32, Option<i32>)>(c)
+                    .map(TagTree::new)
+            }?;
+
+            let tagged = {
+                use schema::item_tags::dsl::*;
+                item_tags
+                    .filter(tag_id.eq_any(&merged))
+                    .select(item_id)
+                    .load::<i32>(c)
+            }?;
+            insert_item_tags(c, uid, &tagged, &[tid])?;
+            {
+                use schema::item_tags::dsl::*;
+                diesel::delete(item_tags.filter(tag_id.eq_any(&merged))).execute(c)
+            }?;
+
+            // A survivor below a merged tag takes the place of the topmost such tag, anything
+            // else would make it its own ancestor
+            if let Some(replaced) = tree
+                .ancestors(tid)
+                .into_iter()
+                .rev()
+                .find(|ancestor| merged.contains(ancestor))
+            {
+                use schema::tags::dsl::*;
+                diesel::update(tags.filter(id.eq(tid)))
+                    .set(parent_id.eq(tree.parent(replaced)))
+                    .execute(c)?;
+            }
+            {
+                use schema::tags::dsl::*;
+                let survivor_name = tags.find(tid).select(tag_name).first::<String>(c)?;
+                let renames = tags
+                    .filter(id.eq_any(&merged))
+                    .select(tag_name)
+                    .load::<String>(c)?
+                    .into_iter()
+                    .map(|name| (name, survivor_name.clone()))
+                    .collect::<HashMap<_, _>>();
+                rename_tag_references(c, uid, &renames)?;
+            }
+            {
+                use schema::tags::dsl::*;
+                diesel::update(tags.filter(parent_id.eq_any(&merged).and(id.ne(tid))))
+                    .set(parent_id.eq(tid))
+                    .execute(c)?;
+                diesel::delete(tags.filter(id.eq_any(&merged))).execute(c)?;
+            }
+
+            load_tags(c, uid, Some(&[tid]))?.pop().ok_or_else(|| {
+                ErrorResponse::new(Status { code: 404 }, "Couldn't load tag".to_string())
+            })
+        })
+    })
+    .await
+    .map(Json)
+}
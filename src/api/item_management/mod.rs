@@ -1,14 +1,28 @@
 pub(crate) mod add_tag;
 pub(crate) mod add_use;
+pub(crate) mod attributes;
+pub(crate) mod bulk_tags;
+pub(crate) mod calendar;
 pub(crate) mod create;
 pub(crate) mod create_tag;
 pub(crate) mod delete;
 pub(crate) mod delete_tag;
+pub(crate) mod delete_use;
 pub(crate) mod edit;
+pub(crate) mod edit_tag;
+pub(crate) mod edit_use;
+pub(crate) mod get_inventory;
 pub(crate) mod get_item;
 pub(crate) mod get_item_tags;
 pub(crate) mod get_tags;
+pub(crate) mod get_uses;
 pub(crate) mod list;
+pub(crate) mod merge_items;
+pub(crate) mod merge_tags;
 pub(crate) mod models;
 pub(crate) mod modify_inventory;
+pub(crate) mod move_tag;
+pub(crate) mod ownership;
+pub(crate) mod queries;
 pub(crate) mod remove_tag;
+pub(crate) mod tag_tree;
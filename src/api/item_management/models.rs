@@ -1,10 +1,97 @@
-use crate::schema::items;
+use crate::schema::{items, tags};
+use chrono::NaiveDate;
+use serde::Serialize;
 use std::fmt::Debug;
 
-#[derive(Queryable, Debug, Identifiable, AsChangeset)]
+#[derive(Queryable, Debug, Clone, Identifiable, AsChangeset)]
 #[table_name = "items"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct Item {
     pub id: i32,
     pub user_id: i32,
     pub item_name: String,
+    pub brand: Option<String>,
+    pub size: Option<String>,
+    pub color: Option<String>,
+    pub material: Option<String>,
+    pub purchase_date: Option<NaiveDate>,
+    pub purchase_price_cents: Option<i32>,
+    pub purchase_currency: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Queryable, Debug, Identifiable, AsChangeset)]
+#[table_name = "tags"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct Tag {
+    pub id: i32,
+    pub user_id: i32,
+    pub tag_name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    /// The tag this one is a subcategory of.
+    pub parent_id: Option<i32>,
+}
+
+/// Why an inventory movement happened, stored as its lowercase name.
+#[derive(FromFormField, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MovementReason {
+    Purchased,
+    Gifted,
+    Discarded,
+    Lost,
+    Sold,
+    Donated,
+    /// Fixes a wrong balance in either direction.
+    Correction,
+}
+
+impl MovementReason {
+    pub const ALL: [MovementReason; 7] = [
+        MovementReason::Purchased,
+        MovementReason::Gifted,
+        MovementReason::Discarded,
+        MovementReason::Lost,
+        MovementReason::Sold,
+        MovementReason::Donated,
+        MovementReason::Correction,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MovementReason::Purchased => "purchased",
+            MovementReason::Gifted => "gifted",
+            MovementReason::Discarded => "discarded",
+            MovementReason::Lost => "lost",
+            MovementReason::Sold => "sold",
+            MovementReason::Donated => "donated",
+            MovementReason::Correction => "correction",
+        }
+    }
+
+    /// What a movement is assumed to be when no reason is given, as before movements had reasons.
+    pub fn default_for(movement: i32) -> MovementReason {
+        if movement > 0 {
+            MovementReason::Purchased
+        } else {
+            MovementReason::Discarded
+        }
+    }
+
+    pub fn parse(reason: &str) -> Option<MovementReason> {
+        MovementReason::ALL
+            .iter()
+            .copied()
+            .find(|known| known.as_str() == reason)
+    }
+
+    /// Whether `movement` goes in the direction the reason implies.
+    pub fn allows(self, movement: i32) -> bool {
+        match self {
+            MovementReason::Purchased | MovementReason::Gifted => movement > 0,
+            MovementReason::Correction => movement != 0,
+            _ => movement < 0,
+        }
+    }
 }
@@ -1,10 +1,11 @@
+use crate::api::item_management::models::MovementReason;
+use crate::api::item_management::queries;
 use crate::api::user_management::models::UserLoggedIn;
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
 use crate::schema;
 use crate::schema::item_inventory;
 use diesel::prelude::*;
-use diesel::sql_types::Integer;
 use rocket::form::Form;
 use rocket::http::Status;
 
@@ -12,6 +13,11 @@ use rocket::http::Status;
 pub struct FormInventory {
     item_id: i32,
     movement: i32,
+    /// Defaults to `purchased` for added and `discarded` for removed items.
+    reason: Option<MovementReason>,
+    note: Option<String>,
+    /// What was paid or received for the items, in cents.
+    value_cents: Option<i32>,
 }
 
 #[derive(Insertable, AsChangeset)]
@@ -19,35 +25,84 @@ pub struct FormInventory {
 pub(super) struct NewInventory {
     pub(super) item_id: i32,
     pub(super) movement: i32,
+    pub(super) reason: &'static str,
+    pub(super) note: Option<String>,
+    pub(super) value_cents: Option<i32>,
 }
 
+/// Records a movement, which has to point in the direction of its reason, e.g. `sold` only
+/// takes items away. Only a `correction` may take more items away than are in the inventory.
 #[post("/modify_inventory", data = "<form_inventory>")]
 pub(crate) async fn modify_inventory(
     form_inventory: Form<FormInventory>,
     user: UserLoggedIn,
     conn: DbConn,
 ) -> Result<&'static str, ErrorResponse> {
-    use schema::items::dsl::*;
+    let form_inventory = form_inventory.into_inner();
+    let reason = form_inventory
+        .reason
+        .unwrap_or_else(|| MovementReason::default_for(form_inventory.movement));
+    if !reason.allows(form_inventory.movement) {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            format!(
+                "Movement {} doesn't match reason {}",
+                form_inventory.movement,
+                reason.as_str()
+            ),
+        ));
+    }
+    if form_inventory.value_cents.is_some_and(|value| value < 0) {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Value must not be negative".to_string(),
+        ));
+    }
 
+    let uid = user.0.id;
     conn.run(move |c| {
-        diesel::insert_into(schema::item_inventory::dsl::item_inventory)
-            .values(
+        c.transaction::<_, ErrorResponse, _>(|| {
+            // Locking the item serialises concurrent movements, so the balance check holds
+            let owned = {
+                use schema::items::dsl::*;
                 items
-                    .filter(user_id.eq(user.0.id).and(id.eq(form_inventory.item_id)))
-                    .limit(1)
-                    .select((id, form_inventory.movement.into_sql::<Integer>())),
-            )
-            .into_columns((
-                schema::item_inventory::columns::item_id,
-                schema::item_inventory::columns::movement,
-            ))
-            .returning(schema::item_inventory::columns::id)
-            .get_result::<i32>(c)
+                    .filter(user_id.eq(uid).and(id.eq(form_inventory.item_id)))
+                    .select(id)
+                    .for_update()
+                    .first::<i32>(c)
+                    .optional()
+            }?;
+            let iid = owned.ok_or_else(|| {
+                ErrorResponse::new(Status { code: 404 }, "Couldn't load item".to_string())
+            })?;
+
+            if reason != MovementReason::Correction && form_inventory.movement < 0 {
+                let balance = queries::inventory_count(c, iid)?;
+                if balance + i64::from(form_inventory.movement) < 0 {
+                    return Err(ErrorResponse::new(
+                        Status { code: 409 },
+                        format!(
+                            "Only {} in inventory, use a correction to go below zero",
+                            balance
+                        ),
+                    ));
+                }
+            }
+
+            diesel::insert_into(item_inventory::table)
+                .values(&NewInventory {
+                    item_id: iid,
+                    movement: form_inventory.movement,
+                    reason: reason.as_str(),
+                    note: form_inventory.note,
+                    value_cents: form_inventory.value_cents,
+                })
+                .execute(c)?;
+
+            Ok(())
+        })
     })
-    .await
-    .map_err(|_| {
-        ErrorResponse::new(Status { code: 500 }, "Error inserting movement".to_string())
-    })?;
+    .await?;
 
     Ok("Success")
 }
@@ -0,0 +1,69 @@
+use crate::api::item_management::create_tag::{check_parent_owned, tag_error};
+use crate::api::item_management::get_tags::{load_tags, TagOut};
+use crate::api::item_management::tag_tree::TagTree;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[derive(FromForm)]
+pub struct FormMoveTag {
+    /// Missing to make the tag a top level one.
+    parent_id: Option<i32>,
+}
+
+/// Places the tag below another one, taking all tags below it along.
+#[post("/tag/<tid>/move", data = "<form_tag>")]
+pub(crate) async fn move_tag(
+    form_tag: Form<FormMoveTag>,
+    tid: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<TagOut>, ErrorResponse> {
+    let new_parent = form_tag.parent_id;
+    let uid = user.0.id;
+
+    conn.run(move |c| {
+        c.transaction::<_, ErrorResponse, _>(|| {
+            // Locking all of the user's tags keeps concurrent moves from forming a cycle
+            let parents = {
+                use schema::tags::dsl::*;
+                tags.filter(user_id.eq(uid))
+                    .select((id, parent_id))
+                    .for_update()
+                    .load::<(i32, Option<i32>)>(c)
+            }?;
+            if !parents.iter().any(|(id, _)| *id == tid) {
+                return Err(tag_error(DieselError::NotFound, ""));
+            }
+
+            if let Some(parent) = new_parent {
+                check_parent_owned(c, uid, parent)?;
+                if TagTree::new(parents).would_cycle(tid, parent) {
+                    return Err(ErrorResponse::new(
+                        Status { code: 400 },
+                        "A tag can't be moved below itself or its descendants".to_string(),
+                    ));
+                }
+            }
+
+            {
+                use schema::tags::dsl::*;
+                diesel::update(tags.filter(id.eq(tid)))
+                    .set(parent_id.eq(new_parent))
+                    .execute(c)
+            }?;
+
+            load_tags(c, uid, Some(&[tid]))?
+                .pop()
+                .ok_or_else(|| tag_error(DieselError::NotFound, ""))
+        })
+    })
+    .await
+    .map(Json)
+}
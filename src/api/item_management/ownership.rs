@@ -0,0 +1,47 @@
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+
+/// The first of `ids` that is missing from `owned`, reported as not found.
+fn check_all_owned(kind: &str, ids: &[i32], owned: &[i32]) -> Result<(), ErrorResponse> {
+    match ids.iter().find(|oid| !owned.contains(oid)) {
+        Some(oid) => Err(ErrorResponse::new(
+            Status { code: 404 },
+            format!("Couldn't load {} {}", kind, oid),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fails with 404 unless every one of `item_ids` belongs to `uid`.
+pub(crate) fn check_items_owned(
+    c: &PgConnection,
+    uid: i32,
+    item_ids: &[i32],
+) -> Result<(), ErrorResponse> {
+    use schema::items::dsl::*;
+
+    let owned = items
+        .filter(user_id.eq(uid).and(id.eq_any(item_ids)))
+        .select(id)
+        .load::<i32>(c)?;
+
+    check_all_owned("item", item_ids, &owned)
+}
+
+/// Fails with 404 unless every one of `tag_ids` belongs to `uid`.
+pub(crate) fn check_tags_owned(
+    c: &PgConnection,
+    uid: i32,
+    tag_ids: &[i32],
+) -> Result<(), ErrorResponse> {
+    use schema::tags::dsl::*;
+
+    let owned = tags
+        .filter(user_id.eq(uid).and(id.eq_any(tag_ids)))
+        .select(id)
+        .load::<i32>(c)?;
+
+    check_all_owned("tag", tag_ids, &owned)
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use crate::schema;
+use chrono::NaiveDate;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Date, Nullable};
+
+pub(crate) fn use_count(c: &PgConnection, iid: i32) -> QueryResult<i64> {
+    use schema::uses::dsl::*;
+
+    uses.filter(item_id.eq(iid)).count().get_result(c)
+}
+
+/// How many of the item are owned, the sum of its inventory movements.
+pub(crate) fn inventory_count(c: &PgConnection, iid: i32) -> QueryResult<i64> {
+    use schema::item_inventory::dsl::*;
+
+    item_inventory
+        .filter(item_id.eq(iid))
+        .select(sql::<Nullable<BigInt>>("SUM(movement)"))
+        .first::<Option<i64>>(c)
+        .map(|count| count.unwrap_or(0))
+}
+
+/// Uses per item. Items that were never used are missing from the map.
+pub(crate) fn use_counts(c: &PgConnection, item_ids: &[i32]) -> QueryResult<HashMap<i32, i64>> {
+    use schema::uses::dsl::*;
+
+    // Diesel 1.4 can't select a column next to an aggregate, hence the literal
+    uses.filter(item_id.eq_any(item_ids))
+        .group_by(item_id)
+        .select((item_id, sql::<BigInt>("COUNT(*)")))
+        .load::<(i32, i64)>(c)
+        .map(|counts| counts.into_iter().collect())
+}
+
+/// Date of the most recent use per item. Items that were never used are missing from the map.
+pub(crate) fn last_uses(
+    c: &PgConnection,
+    item_ids: &[i32],
+) -> QueryResult<HashMap<i32, NaiveDate>> {
+    use schema::uses::dsl::*;
+
+    uses.filter(item_id.eq_any(item_ids))
+        .group_by(item_id)
+        .select((item_id, sql::<Date>("MAX(date)")))
+        .load::<(i32, NaiveDate)>(c)
+        .map(|dates| dates.into_iter().collect())
+}
+
+/// Sum of all inventory movements per item, i.e. how many of it are owned right now.
+pub(crate) fn inventory_counts(
+    c: &PgConnection,
+    item_ids: &[i32],
+) -> QueryResult<HashMap<i32, i64>> {
+    use schema::item_inventory::dsl::*;
+
+    item_inventory
+        .filter(item_id.eq_any(item_ids))
+        .group_by(item_id)
+        .select((item_id, sql::<BigInt>("SUM(movement)")))
+        .load::<(i32, i64)>(c)
+        .map(|counts| counts.into_iter().collect())
+}
@@ -18,6 +18,33 @@ pub struct FormTag {
     tag_id: i32,
 }
 
+/// Removes every one of `tag_ids` from every one of `item_ids`, returning the removed
+/// `(item_id, tag_id)` pairs. Tags of other users are skipped.
+pub(super) fn delete_item_tags(
+    c: &PgConnection,
+    uid: i32,
+    item_ids: &[i32],
+    tag_ids: &[i32],
+) -> QueryResult<Vec<(i32, i32)>> {
+    use schema::item_tags;
+    use schema::tags;
+
+    // TODO Probably better to use a join here in the future once supported by diesel
+    // https://github.com/diesel-rs/diesel/issues/1478
+    diesel::delete(item_tags::table)
+        .filter(item_tags::item_id.eq_any(item_ids))
+        .filter(
+            item_tags::tag_id.eq_any(
+                tags::table
+                    .filter(tags::user_id.eq(uid))
+                    .filter(tags::id.eq_any(tag_ids))
+                    .select(tags::id),
+            ),
+        )
+        .returning((item_tags::item_id, item_tags::tag_id))
+        .get_results(c)
+}
+
 #[post("/item/<item>/remove_tag", data = "<form_tag>")]
 pub(crate) async fn remove_tag(
     item: i32,
@@ -25,26 +52,9 @@ pub(crate) async fn remove_tag(
     conn: DbConn,
     form_tag: Form<FormTag>,
 ) -> Result<(), ErrorResponse> {
-    conn.run::<_, Result<(), diesel::result::Error>>(move |c| {
-        use schema::item_tags;
-        use schema::tags;
-        // TODO Probably better to use a join here in the future once supported by diesel
-        // https://github.com/diesel-rs/diesel/issues/1478
-        diesel::delete(item_tags::table)
-            .filter(item_tags::item_id.eq(item))
-            .filter(
-                item_tags::tag_id.eq_any(
-                    tags::table
-                        .filter(tags::user_id.eq(user.0.id))
-                        .filter(tags::id.eq(form_tag.tag_id))
-                        .select(tags::id),
-                ),
-            )
-            .execute(c)
-            .map(|_| (()))
-    })
-    .await
-    .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't remove tag".to_string()))?;
+    conn.run(move |c| delete_item_tags(c, user.0.id, &[item], &[form_tag.tag_id]))
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't remove tag".to_string()))?;
 
     Ok(())
 }
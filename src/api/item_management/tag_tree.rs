@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::schema;
+use diesel::prelude::*;
+
+/// The parent of every tag of a user, which is how tags form categories like
+/// `Tops > Shirts > Oxford`.
+pub(crate) struct TagTree {
+    parents: HashMap<i32, Option<i32>>,
+    children: HashMap<i32, Vec<i32>>,
+}
+
+impl TagTree {
+    pub(crate) fn new(parents: impl IntoIterator<Item = (i32, Option<i32>)>) -> Self {
+        let parents = parents.into_iter().collect::<HashMap<_, _>>();
+        let mut children = HashMap::<i32, Vec<i32>>::new();
+        for (child, parent) in &parents {
+            if let Some(parent) = parent {
+                children.entry(*parent).or_default().push(*child);
+            }
+        }
+
+        TagTree { parents, children }
+    }
+
+    pub(crate) fn load(c: &PgConnection, uid: i32) -> QueryResult<Self> {
+        use schema::tags::dsl::*;
+
+        tags.filter(user_id.eq(uid))
+            .select((id, parent_id))
+            .load::<(i32, Option<i32>)>(c)
+            .map(TagTree::new)
+    }
+
+    pub(crate) fn parent(&self, tag: i32) -> Option<i32> {
+        self.parents.get(&tag).copied().flatten()
+    }
+
+    /// Parent, grandparent and so on of `tag`, nearest first.
+    pub(crate) fn ancestors(&self, tag: i32) -> Vec<i32> {
+        let mut ancestors = Vec::new();
+        let mut current = tag;
+        while let Some(parent) = self.parent(current) {
+            // Guards against looping forever should the tree ever contain a cycle
+            if parent == tag || ancestors.contains(&parent) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// `tag` and all tags below it.
+    pub(crate) fn subtree(&self, tag: i32) -> HashSet<i32> {
+        let mut subtree = HashSet::from([tag]);
+        let mut pending = vec![tag];
+        while let Some(current) = pending.pop() {
+            for child in self.children.get(&current).into_iter().flatten() {
+                if subtree.insert(*child) {
+                    pending.push(*child);
+                }
+            }
+        }
+        subtree
+    }
+
+    /// Whether placing `tag` below `parent` would make the tag its own ancestor.
+    pub(crate) fn would_cycle(&self, tag: i32, parent: i32) -> bool {
+        parent == tag || self.ancestors(parent).contains(&tag)
+    }
+
+    /// A tag that is its own ancestor, if the tree isn't a tree at all.
+    pub(crate) fn find_cycle(&self) -> Option<i32> {
+        self.parents.iter().find_map(|(tag, parent)| {
+            parent
+                .filter(|parent| self.would_cycle(*tag, *parent))
+                .map(|_| *tag)
+        })
+    }
+}
+
+/// Tags an item has to carry one of to match a filter by `tag`, which with `descendants` includes
+/// all tags below it.
+pub(crate) fn filter_tags(
+    c: &PgConnection,
+    uid: i32,
+    tag: i32,
+    descendants: bool,
+) -> QueryResult<Vec<i32>> {
+    if descendants {
+        Ok(TagTree::load(c, uid)?.subtree(tag).into_iter().collect())
+    } else {
+        Ok(vec![tag])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tops > Shirts > Oxford, Tops > Jumpers and Hats without a parent.
+    fn tree() -> TagTree {
+        TagTree::new([
+            (1, None),
+            (2, Some(1)),
+            (3, Some(2)),
+            (4, Some(1)),
+            (5, None),
+        ])
+    }
+
+    #[test]
+    fn ancestors_are_nearest_first() {
+        let tree = tree();
+        assert_eq!(tree.ancestors(3), vec![2, 1]);
+        assert_eq!(tree.ancestors(1), Vec::<i32>::new());
+        assert_eq!(tree.ancestors(42), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn subtree_contains_tag_and_all_below_it() {
+        let tree = tree();
+        assert_eq!(tree.subtree(1), HashSet::from([1, 2, 3, 4]));
+        assert_eq!(tree.subtree(2), HashSet::from([2, 3]));
+        assert_eq!(tree.subtree(5), HashSet::from([5]));
+        assert_eq!(tree.subtree(42), HashSet::from([42]));
+    }
+
+    #[test]
+    fn moving_below_itself_or_descendants_cycles() {
+        let tree = tree();
+        assert!(tree.would_cycle(1, 1));
+        assert!(tree.would_cycle(1, 2));
+        assert!(tree.would_cycle(1, 3));
+        assert!(tree.would_cycle(2, 3));
+        assert!(!tree.would_cycle(3, 1));
+        assert!(!tree.would_cycle(2, 4));
+        assert!(!tree.would_cycle(1, 5));
+    }
+
+    #[test]
+    fn finds_cycles() {
+        assert_eq!(tree().find_cycle(), None);
+        assert_eq!(TagTree::new([(1, Some(1))]).find_cycle(), Some(1));
+
+        let cycle = TagTree::new([(1, Some(3)), (2, Some(1)), (3, Some(2)), (4, Some(1))]);
+        assert!(matches!(cycle.find_cycle(), Some(1..=3)));
+        // Walking up the cycle ends instead of looping forever
+        assert_eq!(cycle.ancestors(4), vec![1, 3, 2]);
+        assert_eq!(cycle.subtree(1), HashSet::from([1, 2, 3, 4]));
+    }
+}
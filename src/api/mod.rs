@@ -1,2 +1,8 @@
+pub mod analytics;
+pub mod data_management;
 pub mod item_management;
+pub mod outfit_management;
+pub mod reports;
+pub mod smart_tag_management;
+pub mod suggestions;
 pub mod user_management;
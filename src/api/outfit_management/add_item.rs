@@ -0,0 +1,51 @@
+use crate::api::item_management::ownership::check_items_owned;
+use crate::api::outfit_management::create::NewOutfitItem;
+use crate::api::outfit_management::models::FormOutfitItem;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+
+/// Adding an item that already is part of the outfit does nothing.
+#[post("/outfit/<outfit>/add_item", data = "<form_item>")]
+pub(crate) async fn add_item(
+    outfit: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+    form_item: Form<FormOutfitItem>,
+) -> Result<(), ErrorResponse> {
+    let uid = user.0.id;
+    let iid = form_item.item_id;
+
+    conn.run(move |c| {
+        let owned = {
+            use schema::outfits::dsl::*;
+            outfits
+                .filter(user_id.eq(uid).and(id.eq(outfit)))
+                .select(id)
+                .first::<i32>(c)
+                .optional()
+        }?;
+        if owned.is_none() {
+            return Err(ErrorResponse::new(
+                Status { code: 404 },
+                "Couldn't load outfit".to_string(),
+            ));
+        }
+        check_items_owned(c, uid, &[iid])?;
+
+        diesel::insert_into(schema::outfit_items::table)
+            .values(&NewOutfitItem {
+                outfit_id: outfit,
+                item_id: iid,
+            })
+            .on_conflict_do_nothing()
+            .execute(c)?;
+
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,70 @@
+use crate::api::item_management::ownership::check_items_owned;
+use crate::api::outfit_management::get_outfit::{load_outfits, OutfitOut};
+use crate::api::outfit_management::models::Outfit;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema::{outfit_items, outfits};
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::serde::json::Json;
+
+#[derive(FromForm)]
+pub struct FormOutfit {
+    name: String,
+    items: Vec<i32>,
+}
+
+#[derive(Insertable)]
+#[table_name = "outfits"]
+struct NewOutfit {
+    user_id: i32,
+    outfit_name: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "outfit_items"]
+pub(super) struct NewOutfitItem {
+    pub(super) outfit_id: i32,
+    pub(super) item_id: i32,
+}
+
+#[post("/outfits/create", data = "<form_outfit>")]
+pub(crate) async fn create_outfit(
+    form_outfit: Form<FormOutfit>,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<OutfitOut>, ErrorResponse> {
+    let form_outfit = form_outfit.into_inner();
+    let uid = user.0.id;
+
+    conn.run(move |c| {
+        c.transaction::<_, ErrorResponse, _>(|| {
+            check_items_owned(c, uid, &form_outfit.items)?;
+
+            let outfit = diesel::insert_into(outfits::table)
+                .values(&NewOutfit {
+                    user_id: uid,
+                    outfit_name: form_outfit.name,
+                })
+                .get_result::<Outfit>(c)?;
+
+            let members = form_outfit
+                .items
+                .iter()
+                .map(|iid| NewOutfitItem {
+                    outfit_id: outfit.id,
+                    item_id: *iid,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(outfit_items::table)
+                .values(&members)
+                .on_conflict_do_nothing()
+                .execute(c)?;
+
+            Ok(load_outfits(c, uid, Some(outfit.id))?.remove(0))
+        })
+    })
+    .await
+    .map(Json)
+}
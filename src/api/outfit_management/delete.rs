@@ -0,0 +1,79 @@
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+
+/// The uses logged by wearing the outfit stay, only their link to the outfit goes.
+#[delete("/outfit/<oid>")]
+pub(crate) async fn delete_outfit(
+    user: UserLoggedIn,
+    oid: i32,
+    conn: DbConn,
+) -> Result<(), ErrorResponse> {
+    let uid = user.0.id;
+
+    let deleted = conn
+        .run(move |c| {
+            c.build_transaction()
+                .read_write()
+                .run::<_, diesel::result::Error, _>(|| {
+                    let owned = {
+                        use schema::outfits::dsl::*;
+                        outfits
+                            .filter(user_id.eq(uid).and(id.eq(oid)))
+                            .select(id)
+                            .first::<i32>(c)
+                            .optional()
+                    }?;
+                    if owned.is_none() {
+                        return Ok(false);
+                    }
+
+                    let wear_ids = {
+                        use schema::outfit_wears::dsl::*;
+                        outfit_wears
+                            .filter(outfit_id.eq(oid))
+                            .select(id)
+                            .load::<i32>(c)
+                    }?;
+                    {
+                        use schema::uses::dsl::*;
+                        diesel::update(uses.filter(outfit_wear_id.eq_any(&wear_ids)))
+                            .set(outfit_wear_id.eq(None::<i32>))
+                            .execute(c)
+                    }?;
+                    {
+                        use schema::outfit_wears::dsl::*;
+                        diesel::delete(outfit_wears.filter(outfit_id.eq(oid))).execute(c)
+                    }?;
+                    {
+                        use schema::outfit_items::dsl::*;
+                        diesel::delete(outfit_items.filter(outfit_id.eq(oid))).execute(c)
+                    }?;
+                    {
+                        use schema::outfits::dsl::*;
+                        diesel::delete(outfits.filter(id.eq(oid))).execute(c)
+                    }?;
+
+                    Ok(true)
+                })
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                "Couldn't delete database entries".to_string(),
+            )
+        })?;
+
+    if !deleted {
+        return Err(ErrorResponse::new(
+            Status { code: 404 },
+            "Couldn't load outfit".to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::api::outfit_management::get_outfit::{load_outfits, OutfitOut};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[derive(FromForm)]
+pub struct FormEditOutfit {
+    name: String,
+}
+
+#[post("/outfit/<outfit>/edit", data = "<form_outfit>")]
+pub(crate) async fn edit_outfit(
+    form_outfit: Form<FormEditOutfit>,
+    outfit: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<OutfitOut>, ErrorResponse> {
+    let uid = user.0.id;
+    let name = form_outfit.into_inner().name;
+
+    conn.run(move |c| {
+        {
+            use schema::outfits::dsl::*;
+            diesel::update(outfits.filter(user_id.eq(uid).and(id.eq(outfit))))
+                .set(outfit_name.eq(name))
+                .execute(c)
+        }?;
+
+        load_outfits(c, uid, Some(outfit))
+    })
+    .await
+    .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't update outfit".to_string()))?
+    .pop()
+    .map(Json)
+    .ok_or_else(|| ErrorResponse::new(Status { code: 404 }, "Couldn't load outfit".to_string()))
+}
@@ -0,0 +1,88 @@
+use crate::api::outfit_management::models::Outfit;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct OutfitOut {
+    pub id: i32,
+    pub outfit_name: String,
+    pub items: Vec<i32>,
+    /// How often the outfit was worn as a whole.
+    pub wear_count: i32,
+    pub last_worn: Option<NaiveDate>,
+}
+
+/// Loads the user's outfits, or only `outfit` if given, with their items and wears.
+pub(crate) fn load_outfits(
+    c: &PgConnection,
+    uid: i32,
+    outfit: Option<i32>,
+) -> QueryResult<Vec<OutfitOut>> {
+    let outfit_list = {
+        use schema::outfits::dsl::*;
+        let mut query = outfits.filter(user_id.eq(uid)).into_boxed();
+        if let Some(outfit) = outfit {
+            query = query.filter(id.eq(outfit));
+        }
+        query.order(id).load::<Outfit>(c)
+    }?;
+    let outfit_ids = outfit_list
+        .iter()
+        .map(|outfit| outfit.id)
+        .collect::<Vec<_>>();
+
+    let members = {
+        use schema::outfit_items::dsl::*;
+        outfit_items
+            .filter(outfit_id.eq_any(&outfit_ids))
+            .order(id)
+            .select((outfit_id, item_id))
+            .load::<(i32, i32)>(c)
+    }?;
+    let wears = {
+        use schema::outfit_wears::dsl::*;
+        outfit_wears
+            .filter(outfit_id.eq_any(&outfit_ids))
+            .select((outfit_id, date))
+            .load::<(i32, NaiveDate)>(c)
+    }?;
+
+    Ok(outfit_list
+        .into_iter()
+        .map(|outfit| {
+            let outfit_wears = wears.iter().filter(|(oid, _)| *oid == outfit.id);
+            OutfitOut {
+                id: outfit.id,
+                items: members
+                    .iter()
+                    .filter(|(oid, _)| *oid == outfit.id)
+                    .map(|(_, iid)| *iid)
+                    .collect(),
+                wear_count: outfit_wears.clone().count() as i32,
+                last_worn: outfit_wears.map(|(_, date)| *date).max(),
+                outfit_name: outfit.outfit_name,
+            }
+        })
+        .collect())
+}
+
+#[get("/outfit/<outfit>")]
+pub(crate) async fn get_outfit(
+    user: UserLoggedIn,
+    outfit: i32,
+    conn: DbConn,
+) -> Result<Json<OutfitOut>, ErrorResponse> {
+    conn.run(move |c| load_outfits(c, user.0.id, Some(outfit)))
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load outfit".to_string()))?
+        .pop()
+        .map(Json)
+        .ok_or_else(|| ErrorResponse::new(Status { code: 404 }, "Couldn't load outfit".to_string()))
+}
@@ -0,0 +1,21 @@
+use crate::api::outfit_management::get_outfit::{load_outfits, OutfitOut};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[get("/outfits")]
+pub(crate) async fn get_outfits(
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<Vec<OutfitOut>>, ErrorResponse> {
+    let outfits = conn
+        .run(move |c| load_outfits(c, user.0.id, None))
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load outfits".to_string())
+        })?;
+
+    Ok(Json(outfits))
+}
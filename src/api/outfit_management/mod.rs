@@ -0,0 +1,9 @@
+pub(crate) mod add_item;
+pub(crate) mod create;
+pub(crate) mod delete;
+pub(crate) mod edit;
+pub(crate) mod get_outfit;
+pub(crate) mod list;
+pub(crate) mod models;
+pub(crate) mod remove_item;
+pub(crate) mod wear;
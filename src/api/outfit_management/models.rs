@@ -0,0 +1,16 @@
+use crate::schema::outfits;
+use std::fmt::Debug;
+
+#[derive(Queryable, Debug, Identifiable, AsChangeset)]
+#[table_name = "outfits"]
+pub struct Outfit {
+    pub id: i32,
+    pub user_id: i32,
+    pub outfit_name: String,
+}
+
+/// The item an outfit route adds or removes.
+#[derive(FromForm)]
+pub struct FormOutfitItem {
+    pub item_id: i32,
+}
@@ -0,0 +1,41 @@
+use crate::api::outfit_management::models::FormOutfitItem;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+
+#[post("/outfit/<outfit>/remove_item", data = "<form_item>")]
+pub(crate) async fn remove_item(
+    outfit: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+    form_item: Form<FormOutfitItem>,
+) -> Result<(), ErrorResponse> {
+    let uid = user.0.id;
+    let iid = form_item.item_id;
+
+    conn.run(move |c| {
+        use schema::outfit_items::dsl::*;
+
+        let owned_outfits = schema::outfits::table
+            .filter(schema::outfits::user_id.eq(uid))
+            .select(schema::outfits::id);
+
+        diesel::delete(
+            outfit_items.filter(
+                outfit_id
+                    .eq(outfit)
+                    .and(outfit_id.eq_any(owned_outfits))
+                    .and(item_id.eq(iid)),
+            ),
+        )
+        .execute(c)
+    })
+    .await
+    .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't remove item".to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::api::item_management::attributes::use_date;
+use crate::api::item_management::get_uses::UseOut;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use crate::schema::outfit_wears;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(FromForm)]
+pub struct FormWear {
+    /// Defaults to today.
+    date: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "outfit_wears"]
+struct NewOutfitWear {
+    outfit_id: i32,
+    date: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+pub struct OutfitWearOut {
+    pub id: i32,
+    pub outfit_id: i32,
+    pub date: NaiveDate,
+    pub uses: Vec<UseOut>,
+}
+
+/// Logs a use of every item in the outfit, all linked to one wear of the outfit.
+#[post("/outfit/<outfit>/wear", data = "<form_wear>")]
+pub(crate) async fn wear_outfit(
+    outfit: i32,
+    form_wear: Option<Form<FormWear>>,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<OutfitWearOut>, ErrorResponse> {
+    let wear_date = form_wear
+        .and_then(|form_wear| form_wear.into_inner().date)
+        .map(|wear_date| use_date(&wear_date))
+        .transpose()?;
+    let uid = user.0.id;
+
+    conn.run(move |c| {
+        c.transaction::<_, ErrorResponse, _>(|| {
+            let owned = {
+                use schema::outfits::dsl::*;
+                outfits
+                    .filter(user_id.eq(uid).and(id.eq(outfit)))
+                    .select(id)
+                    .first::<i32>(c)
+                    .optional()
+            }?;
+            if owned.is_none() {
+                return Err(ErrorResponse::new(
+                    Status { code: 404 },
+                    "Couldn't load outfit".to_string(),
+                ));
+            }
+
+            let item_ids = {
+                use schema::outfit_items::dsl::*;
+                outfit_items
+                    .filter(outfit_id.eq(outfit))
+                    .select(item_id)
+                    .load::<i32>(c)
+            }?;
+            if item_ids.is_empty() {
+                return Err(ErrorResponse::new(
+                    Status { code: 400 },
+                    "Outfit has no items".to_string(),
+                ));
+            }
+
+            let (wear_id, worn_on) = diesel::insert_into(outfit_wears::table)
+                .values(&NewOutfitWear {
+                    outfit_id: outfit,
+                    date: wear_date,
+                })
+                .returning((outfit_wears::id, outfit_wears::date))
+                .get_result::<(i32, NaiveDate)>(c)?;
+
+            let new_uses = {
+                use schema::uses::dsl::*;
+                let rows = item_ids
+                    .iter()
+                    .map(|iid| {
+                        (
+                            item_id.eq(*iid),
+                            date.eq(worn_on),
+                            outfit_wear_id.eq(wear_id),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                diesel::insert_into(uses)
+                    .values(&rows)
+                    .get_results::<UseOut>(c)
+            }?;
+
+            Ok(OutfitWearOut {
+                id: wear_id,
+                outfit_id: outfit,
+                date: worn_on,
+                uses: new_uses,
+            })
+        })
+    })
+    .await
+    .map(Json)
+}
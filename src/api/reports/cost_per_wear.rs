@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use crate::api::item_management::get_item::cost_per_wear;
+use crate::api::item_management::queries::use_counts;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct ItemCostPerWear {
+    pub id: i32,
+    pub item_name: String,
+    pub purchase_price_cents: i32,
+    pub purchase_currency: Option<String>,
+    pub uses: i64,
+    pub cost_per_wear_cents: f64,
+    /// Further wears until the cost per wear reaches the requested target.
+    pub wears_needed: Option<i64>,
+}
+
+/// Prices only add up within one currency, so every total is per currency.
+#[derive(Serialize)]
+pub struct Total {
+    pub currency: Option<String>,
+    pub items: usize,
+    pub purchase_price_cents: i64,
+    pub uses: i64,
+    pub cost_per_wear_cents: f64,
+}
+
+#[derive(Serialize)]
+pub struct TagTotal {
+    pub tag_id: i32,
+    pub tag_name: String,
+    #[serde(flatten)]
+    pub total: Total,
+}
+
+#[derive(Serialize)]
+pub struct CostPerWearReport {
+    /// Most expensive per wear first.
+    pub items: Vec<ItemCostPerWear>,
+    pub tags: Vec<TagTotal>,
+    pub totals: Vec<Total>,
+    /// Items without a purchase price, which can't be part of the report.
+    pub unpriced_items: usize,
+}
+
+#[derive(Default)]
+struct Sum {
+    items: usize,
+    purchase_price_cents: i64,
+    uses: i64,
+}
+
+impl Sum {
+    fn add(&mut self, item: &ItemCostPerWear) {
+        self.items += 1;
+        self.purchase_price_cents += i64::from(item.purchase_price_cents);
+        self.uses += item.uses;
+    }
+
+    fn into_total(self, currency: Option<String>) -> Total {
+        Total {
+            currency,
+            items: self.items,
+            purchase_price_cents: self.purchase_price_cents,
+            uses: self.uses,
+            cost_per_wear_cents: cost_per_wear(self.purchase_price_cents, self.uses),
+        }
+    }
+}
+
+fn wears_needed(price_cents: i32, uses: i64, target_cents: i32) -> i64 {
+    let price_cents = i64::from(price_cents);
+    let target_cents = i64::from(target_cents);
+    let total_wears = (price_cents + target_cents - 1) / target_cents;
+
+    (total_wears - uses).max(0)
+}
+
+/// Ranks the user's items by cost per wear. With `target_cents`, each item also tells how many
+/// more wears it takes to get down to that cost per wear.
+#[get("/reports/cost_per_wear?<target_cents>")]
+pub(crate) async fn cost_per_wear_report(
+    user: UserLoggedIn,
+    target_cents: Option<i32>,
+    conn: DbConn,
+) -> Result<Json<CostPerWearReport>, ErrorResponse> {
+    if target_cents.is_some_and(|target| target <= 0) {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Target must be a positive number of cents".to_string(),
+        ));
+    }
+
+    let uid = user.0.id;
+    let (priced, unpriced_items, counts, item_tags) = conn
+        .run(move |c| {
+            let all_items = {
+                use schema::items::dsl::*;
+                items
+                    .filter(user_id.eq(uid))
+                    .select((id, item_name, purchase_price_cents, purchase_currency))
+                    .load::<(i32, String, Option<i32>, Option<String>)>(c)
+            }?;
+            let unpriced_items = all_items
+                .iter()
+                .filter(|(_, _, price, _)| price.is_none())
+                .count();
+            let priced = all_items
+                .into_iter()
+                .filter_map(|(iid, name, price, currency)| {
+                    price.map(|price| (iid, name, price, currency))
+                })
+                .collect::<Vec<_>>();
+            let item_ids = priced.iter().map(|item| item.0).collect::<Vec<_>>();
+
+            let counts = use_counts(c, &item_ids)?;
+            let item_tags = {
+                use schema::item_tags;
+                use schema::tags;
+                item_tags::table
+                    .inner_join(tags::table)
+                    .filter(tags::user_id.eq(uid))
+                    .filter(item_tags::item_id.eq_any(&item_ids))
+                    .select((item_tags::item_id, tags::id, tags::tag_name))
+                    .load::<(i32, i32, String)>(c)
+            }?;
+
+            Ok::<_, diesel::result::Error>((priced, unpriced_items, counts, item_tags))
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load items".to_string()))?;
+
+    let mut items = priced
+        .into_iter()
+        .map(|(iid, name, price, currency)| {
+            let uses = counts.get(&iid).copied().unwrap_or(0);
+            ItemCostPerWear {
+                id: iid,
+                item_name: name,
+                purchase_price_cents: price,
+                purchase_currency: currency,
+                uses,
+                cost_per_wear_cents: cost_per_wear(price.into(), uses),
+                wears_needed: target_cents.map(|target| wears_needed(price, uses, target)),
+            }
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| {
+        b.cost_per_wear_cents
+            .total_cmp(&a.cost_per_wear_cents)
+            .then_with(|| a.item_name.cmp(&b.item_name))
+    });
+
+    let mut totals = BTreeMap::<Option<String>, Sum>::new();
+    for item in &items {
+        totals
+            .entry(item.purchase_currency.clone())
+            .or_default()
+            .add(item);
+    }
+
+    let mut tag_totals = BTreeMap::<(String, i32, Option<String>), Sum>::new();
+    for (iid, tid, tag_name) in item_tags {
+        if let Some(item) = items.iter().find(|item| item.id == iid) {
+            tag_totals
+                .entry((tag_name, tid, item.purchase_currency.clone()))
+                .or_default()
+                .add(item);
+        }
+    }
+
+    Ok(Json(CostPerWearReport {
+        tags: tag_totals
+            .into_iter()
+            .map(|((tag_name, tag_id, currency), sum)| TagTotal {
+                tag_id,
+                tag_name,
+                total: sum.into_total(currency),
+            })
+            .collect(),
+        totals: totals
+            .into_iter()
+            .map(|(currency, sum)| sum.into_total(currency))
+            .collect(),
+        items,
+        unpriced_items,
+    }))
+}
@@ -0,0 +1,3 @@
+pub(crate) mod cost_per_wear;
+pub(crate) mod neglected;
+pub(crate) mod outflow;
@@ -0,0 +1,119 @@
+use crate::api::item_management::queries::{inventory_counts, last_uses};
+use crate::api::item_management::tag_tree::filter_tags;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::{Local, NaiveDate};
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+const DEFAULT_THRESHOLD_DAYS: i64 = 90;
+
+#[derive(Serialize)]
+pub struct NeglectedItem {
+    pub item_id: i32,
+    pub item_name: String,
+    /// Missing if the item was never used.
+    pub last_used: Option<NaiveDate>,
+    pub idle_days: Option<i64>,
+    /// How many of the item are owned according to the inventory.
+    pub inventory: i64,
+}
+
+/// Items that weren't used in the last `days` days, 90 by default, or never at all.
+///
+/// `order` is `desc`, the default, to list the longest idle items first, or `asc`. Never used
+/// items count as idle the longest. `tag` only includes items with that tag, or with
+/// `descendants` also those with a tag below it.
+#[get("/reports/neglected?<days>&<tag>&<descendants>&<order>")]
+pub(crate) async fn neglected_items(
+    user: UserLoggedIn,
+    days: Option<i64>,
+    tag: Option<i32>,
+    descendants: bool,
+    order: Option<String>,
+    conn: DbConn,
+) -> Result<Json<Vec<NeglectedItem>>, ErrorResponse> {
+    let threshold = days.unwrap_or(DEFAULT_THRESHOLD_DAYS);
+    if threshold < 0 {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Days must not be negative".to_string(),
+        ));
+    }
+    let most_idle_first = match order.as_deref() {
+        None | Some("desc") => true,
+        Some("asc") => false,
+        Some(order) => {
+            return Err(ErrorResponse::new(
+                Status { code: 400 },
+                format!("Unknown order {}, expected asc or desc", order),
+            ))
+        }
+    };
+
+    let uid = user.0.id;
+    let (item_list, last_used, inventory) = conn
+        .run(move |c| {
+            let item_list = {
+                use schema::item_tags;
+                use schema::items;
+                use schema::tags;
+
+                let mut query = items::table
+                    .filter(items::user_id.eq(uid))
+                    .select((items::id, items::item_name))
+                    .into_boxed();
+                if let Some(tag) = tag {
+                    let tag_ids = filter_tags(c, uid, tag, descendants)?;
+                    let tagged = item_tags::table
+                        .inner_join(tags::table)
+                        .filter(tags::user_id.eq(uid).and(tags::id.eq_any(tag_ids)))
+                        .select(item_tags::item_id);
+                    query = query.filter(items::id.eq_any(tagged));
+                }
+                query.load::<(i32, String)>(c)
+            }?;
+            let item_ids = item_list.iter().map(|(iid, _)| *iid).collect::<Vec<_>>();
+
+            Ok::<_, diesel::result::Error>((
+                item_list,
+                last_uses(c, &item_ids)?,
+                inventory_counts(c, &item_ids)?,
+            ))
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load items".to_string()))?;
+
+    let today = Local::now().date_naive();
+    let mut neglected = item_list
+        .into_iter()
+        .map(|(iid, name)| {
+            let last = last_used.get(&iid).copied();
+            NeglectedItem {
+                item_id: iid,
+                item_name: name,
+                last_used: last,
+                idle_days: last.map(|last| (today - last).num_days()),
+                inventory: inventory.get(&iid).copied().unwrap_or(0),
+            }
+        })
+        .filter(|item| item.idle_days.is_none_or(|idle| idle >= threshold))
+        .collect::<Vec<_>>();
+
+    // Never used items have been idle for longer than any used one
+    let idle = |item: &NeglectedItem| item.idle_days.unwrap_or(i64::MAX);
+    neglected.sort_by(|a, b| {
+        let by_idle = if most_idle_first {
+            idle(b).cmp(&idle(a))
+        } else {
+            idle(a).cmp(&idle(b))
+        };
+        by_idle.then_with(|| a.item_name.cmp(&b.item_name))
+    });
+
+    Ok(Json(neglected))
+}
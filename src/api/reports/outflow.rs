@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use crate::api::analytics::queries::DateRange;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Queryable, Serialize)]
+pub struct Outflow {
+    pub id: i32,
+    pub item_id: i32,
+    pub item_name: String,
+    /// Number of items that left, always positive. Wide enough to negate any movement.
+    #[diesel(deserialize_as = "i32")]
+    pub quantity: i64,
+    pub reason: String,
+    pub note: Option<String>,
+    pub value_cents: Option<i32>,
+    pub update_time: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct ReasonTotal {
+    pub reason: String,
+    pub movements: usize,
+    pub quantity: i64,
+    pub value_cents: i64,
+}
+
+#[derive(Serialize)]
+pub struct OutflowReport {
+    pub reasons: Vec<ReasonTotal>,
+    /// Newest first.
+    pub movements: Vec<Outflow>,
+}
+
+/// Everything that left the inventory between `from` and `to`, and why.
+#[get("/reports/outflow?<from>&<to>")]
+pub(crate) async fn outflow_report(
+    user: UserLoggedIn,
+    from: Option<String>,
+    to: Option<String>,
+    conn: DbConn,
+) -> Result<Json<OutflowReport>, ErrorResponse> {
+    let range = DateRange::parse(from, to)?;
+
+    let uid = user.0.id;
+    let movements = conn
+        .run(move |c| {
+            use schema::item_inventory;
+            use schema::items;
+
+            let mut query = item_inventory::table
+                .inner_join(items::table.on(items::id.eq(item_inventory::item_id)))
+                .filter(items::user_id.eq(uid))
+                .filter(item_inventory::movement.lt(0))
+                .select((
+                    item_inventory::id,
+                    items::id,
+                    items::item_name,
+                    item_inventory::movement,
+                    item_inventory::reason,
+                    item_inventory::note,
+                    item_inventory::value_cents,
+                    item_inventory::update_time,
+                ))
+                .into_boxed();
+            if let Some(from) = range.from {
+                query = query.filter(item_inventory::update_time.ge(from.and_time(NaiveTime::MIN)));
+            }
+            if let Some(to) = range.to {
+                let end = (to + Duration::days(1)).and_time(NaiveTime::MIN);
+                query = query.filter(item_inventory::update_time.lt(end));
+            }
+
+            query
+                .order((
+                    item_inventory::update_time.desc(),
+                    item_inventory::id.desc(),
+                ))
+                .load::<Outflow>(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load inventory".to_string())
+        })?
+        .into_iter()
+        .map(|outflow| Outflow {
+            quantity: -outflow.quantity,
+            ..outflow
+        })
+        .collect::<Vec<_>>();
+
+    let mut reasons = BTreeMap::<&str, ReasonTotal>::new();
+    for outflow in &movements {
+        let total = reasons
+            .entry(&outflow.reason)
+            .or_insert_with(|| ReasonTotal {
+                reason: outflow.reason.clone(),
+                movements: 0,
+                quantity: 0,
+                value_cents: 0,
+            });
+        total.movements += 1;
+        total.quantity += outflow.quantity;
+        total.value_cents += i64::from(outflow.value_cents.unwrap_or(0));
+    }
+
+    Ok(Json(OutflowReport {
+        reasons: reasons.into_values().collect(),
+        movements,
+    }))
+}
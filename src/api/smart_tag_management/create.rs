@@ -0,0 +1,113 @@
+use crate::api::item_management::attributes;
+use crate::api::smart_tag_management::filter::{parse, FilterContext};
+use crate::api::smart_tag_management::list::{load_smart_tags, SmartTagOut};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use crate::schema::smart_tags;
+use chrono::Local;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[derive(Insertable)]
+#[table_name = "smart_tags"]
+struct NewSmartTag {
+    user_id: i32,
+    smart_tag_name: String,
+    expression: String,
+}
+
+#[derive(FromForm)]
+pub struct FormSmartTag {
+    name: String,
+    expression: String,
+}
+
+pub(crate) fn smart_tag_name(value: String) -> Result<String, ErrorResponse> {
+    attributes::text(Some(value)).flatten().ok_or_else(|| {
+        ErrorResponse::new(
+            Status { code: 400 },
+            "Smart tag name must not be empty".to_string(),
+        )
+    })
+}
+
+/// Fails with 400 unless the expression parses and only refers to tags the user has.
+pub(super) fn check_expression(
+    c: &PgConnection,
+    uid: i32,
+    expression: &str,
+) -> Result<(), ErrorResponse> {
+    let filter = parse(expression).map_err(|err| ErrorResponse::new(Status { code: 400 }, err))?;
+    let context = FilterContext::load(c, uid, Local::now().date_naive())?;
+
+    match filter
+        .tag_names()
+        .into_iter()
+        .find(|name| !context.tags.contains_key(*name))
+    {
+        Some(name) => Err(ErrorResponse::new(
+            Status { code: 400 },
+            format!("Unknown tag {}", name),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Smart tag names are unique per user.
+pub(super) fn smart_tag_error(err: DieselError, name: &str) -> ErrorResponse {
+    match err {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => ErrorResponse::new(
+            Status { code: 409 },
+            format!("Smart tag {} already exists", name),
+        ),
+        DieselError::NotFound => {
+            ErrorResponse::new(Status { code: 404 }, "Couldn't load smart tag".to_string())
+        }
+        err => ErrorResponse::new(
+            Status { code: 500 },
+            format!("Couldn't save smart tag: {}", err),
+        ),
+    }
+}
+
+/// Saves a filter expression under a name, see [`crate::api::smart_tag_management::filter`] for
+/// the syntax.
+#[post("/smart_tags/create", data = "<form_smart_tag>")]
+pub(crate) async fn create_smart_tag(
+    form_smart_tag: Form<FormSmartTag>,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<SmartTagOut>, ErrorResponse> {
+    let form_smart_tag = form_smart_tag.into_inner();
+    let uid = user.0.id;
+    let smart_tag = NewSmartTag {
+        user_id: uid,
+        smart_tag_name: smart_tag_name(form_smart_tag.name)?,
+        expression: form_smart_tag.expression.trim().to_string(),
+    };
+
+    conn.run(move |c| {
+        check_expression(c, uid, &smart_tag.expression)?;
+
+        let sid = {
+            use schema::smart_tags::dsl::*;
+            diesel::insert_into(smart_tags)
+                .values(&smart_tag)
+                .returning(id)
+                .get_result::<i32>(c)
+        }
+        .map_err(|err| smart_tag_error(err, &smart_tag.smart_tag_name))?;
+
+        load_smart_tags(c, uid, Some(sid))
+            .map_err(|err| smart_tag_error(err, &smart_tag.smart_tag_name))?
+            .pop()
+            .map(Json)
+            .ok_or_else(|| smart_tag_error(DieselError::NotFound, &smart_tag.smart_tag_name))
+    })
+    .await
+}
@@ -0,0 +1,37 @@
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+
+#[delete("/smart_tag/<sid>")]
+pub(crate) async fn delete_smart_tag(
+    user: UserLoggedIn,
+    sid: i32,
+    conn: DbConn,
+) -> Result<(), ErrorResponse> {
+    let uid = user.0.id;
+
+    let deleted = conn
+        .run(move |c| {
+            use schema::smart_tags::dsl::*;
+            diesel::delete(smart_tags.filter(user_id.eq(uid).and(id.eq(sid)))).execute(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                "Couldn't delete database entries".to_string(),
+            )
+        })?;
+
+    if deleted == 0 {
+        return Err(ErrorResponse::new(
+            Status { code: 404 },
+            "Couldn't load smart tag".to_string(),
+        ));
+    }
+
+    Ok(())
+}
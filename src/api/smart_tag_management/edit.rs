@@ -0,0 +1,61 @@
+use crate::api::smart_tag_management::create::{check_expression, smart_tag_error, smart_tag_name};
+use crate::api::smart_tag_management::list::{load_smart_tags, SmartTagOut};
+use crate::api::smart_tag_management::models::SmartTag;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use rocket::form::Form;
+use rocket::serde::json::Json;
+
+#[derive(FromForm)]
+pub struct FormEditSmartTag {
+    name: Option<String>,
+    expression: Option<String>,
+}
+
+#[post("/smart_tag/<sid>/edit", data = "<form_smart_tag>")]
+pub(crate) async fn edit_smart_tag(
+    form_smart_tag: Form<FormEditSmartTag>,
+    sid: i32,
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<SmartTagOut>, ErrorResponse> {
+    let form_smart_tag = form_smart_tag.into_inner();
+    let new_name = form_smart_tag.name.map(smart_tag_name).transpose()?;
+    let new_expression = form_smart_tag
+        .expression
+        .map(|expression| expression.trim().to_string());
+    let uid = user.0.id;
+
+    conn.run(move |c| {
+        let mut smart_tag = {
+            use schema::smart_tags::dsl::*;
+            smart_tags
+                .filter(user_id.eq(uid).and(id.eq(sid)))
+                .first::<SmartTag>(c)
+        }
+        .map_err(|err| smart_tag_error(err, ""))?;
+
+        if let Some(new_name) = new_name {
+            smart_tag.smart_tag_name = new_name;
+        }
+        if let Some(new_expression) = new_expression {
+            check_expression(c, uid, &new_expression)?;
+            smart_tag.expression = new_expression;
+        }
+
+        let smart_tag = smart_tag
+            .save_changes::<SmartTag>(c)
+            .map_err(|err| smart_tag_error(err, &smart_tag.smart_tag_name))?;
+
+        load_smart_tags(c, uid, Some(smart_tag.id))
+            .map_err(|err| smart_tag_error(err, &smart_tag.smart_tag_name))?
+            .pop()
+            .map(Json)
+            .ok_or_else(|| smart_tag_error(DieselError::NotFound, &smart_tag.smart_tag_name))
+    })
+    .await
+}
@@ -0,0 +1,608 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::api::item_management::tag_tree::TagTree;
+use crate::schema::{item_tags, items, tags, uses};
+use chrono::{Duration, NaiveDate};
+use diesel::dsl::not;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_types::Bool;
+
+// Smart tags select items with expressions like
+//
+//     tag:summer AND NOT (tag:formal OR name:"tux") AND unused:60
+//
+// `tag:` matches items with the tag or a tag below it, `used:N` and `unused:N` items that were or
+// weren't used in the last N days, and `name:` items whose name contains the text. Values with
+// spaces go in double quotes. NOT binds tighter than AND, which binds tighter than OR.
+
+/// Keep long or deeply nested expressions from exhausting the stack.
+const MAX_LENGTH: usize = 1000;
+const MAX_DEPTH: usize = 32;
+const MAX_DAYS: i64 = 36500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Filter {
+    Tag(String),
+    UsedWithin(i64),
+    UnusedFor(i64),
+    NameContains(String),
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(String, String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Open => "(".to_string(),
+            Token::Close => ")".to_string(),
+            Token::And => "AND".to_string(),
+            Token::Or => "OR".to_string(),
+            Token::Not => "NOT".to_string(),
+            Token::Term(key, value) => format!("{}:{}", key, value),
+        }
+    }
+}
+
+/// Splits the expression into tokens along with where they are in it, for terms where their value
+/// is, including any quotes.
+fn tokenize(expression: &str) -> Result<Vec<(Token, Range<usize>)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+
+    while let Some(&(start, next)) = chars.peek() {
+        if next.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if next == '(' || next == ')' {
+            chars.next();
+            let token = if next == '(' {
+                Token::Open
+            } else {
+                Token::Close
+            };
+            tokens.push((token, start..start + 1));
+            continue;
+        }
+
+        let mut word = String::new();
+        while let Some(&(_, c)) = chars.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' || c == ':' {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+
+        if chars.next_if(|(_, c)| *c == ':').is_none() {
+            let token = match word.to_ascii_uppercase().as_str() {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NOT" => Token::Not,
+                _ => {
+                    return Err(format!(
+                        "Unexpected {}, expected a term like tag:name",
+                        word
+                    ))
+                }
+            };
+            tokens.push((token, start..start + word.len()));
+            continue;
+        }
+
+        let value_start = chars.peek().map_or(expression.len(), |(i, _)| *i);
+        let mut value = String::new();
+        if chars.next_if(|(_, c)| *c == '"').is_some() {
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, c)) => value.push(c),
+                    None => return Err(format!("Missing closing quote after {}:", word)),
+                }
+            }
+        } else {
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_whitespace() || c == '(' || c == ')' {
+                    break;
+                }
+                value.push(c);
+                chars.next();
+            }
+        }
+        let value_end = chars.peek().map_or(expression.len(), |(i, _)| *i);
+        tokens.push((
+            Token::Term(word.to_ascii_lowercase(), value),
+            value_start..value_end,
+        ));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.position += 1;
+        self.tokens.get(self.position - 1)
+    }
+
+    fn or(&mut self, depth: usize) -> Result<Filter, String> {
+        let mut filter = self.and(depth)?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            filter = Filter::Or(Box::new(filter), Box::new(self.and(depth)?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self, depth: usize) -> Result<Filter, String> {
+        let mut filter = self.not(depth)?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            filter = Filter::And(Box::new(filter), Box::new(self.not(depth)?));
+        }
+        Ok(filter)
+    }
+
+    fn not(&mut self, depth: usize) -> Result<Filter, String> {
+        if depth > MAX_DEPTH {
+            return Err("Expression is nested too deeply".to_string());
+        }
+
+        match self.next() {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.not(depth + 1)?))),
+            Some(Token::Open) => {
+                let filter = self.or(depth + 1)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err("Missing closing parenthesis".to_string()),
+                }
+            }
+            Some(Token::Term(key, value)) => term(key, value),
+            Some(token) => Err(format!("Unexpected {}, expected a term", token.describe())),
+            None => Err("Unexpected end of expression, expected a term".to_string()),
+        }
+    }
+}
+
+fn term(key: &str, value: &str) -> Result<Filter, String> {
+    let days = || match value.parse::<i64>() {
+        Ok(days) if (0..=MAX_DAYS).contains(&days) => Ok(days),
+        _ => Err(format!(
+            "Invalid {}:{}, expected a number of days up to {}",
+            key, value, MAX_DAYS
+        )),
+    };
+
+    if value.is_empty() {
+        return Err(format!("Missing value after {}:", key));
+    }
+    match key {
+        "tag" => Ok(Filter::Tag(value.to_string())),
+        "used" => Ok(Filter::UsedWithin(days()?)),
+        "unused" => Ok(Filter::UnusedFor(days()?)),
+        "name" => Ok(Filter::NameContains(value.to_string())),
+        _ => Err(format!(
+            "Unknown term {}:, expected tag:, used:, unused: or name:",
+            key
+        )),
+    }
+}
+
+pub(crate) fn parse(expression: &str) -> Result<Filter, String> {
+    if expression.len() > MAX_LENGTH {
+        return Err(format!(
+            "Expression is longer than {} characters",
+            MAX_LENGTH
+        ));
+    }
+
+    let mut parser = Parser {
+        tokens: tokenize(expression)?
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect(),
+        position: 0,
+    };
+    let filter = parser.or(0)?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(Token::Close) => Err("Unexpected closing parenthesis".to_string()),
+        Some(_) => Err("Expected AND or OR between terms".to_string()),
+    }
+}
+
+impl Filter {
+    /// Names of all tags the filter refers to.
+    pub(crate) fn tag_names(&self) -> Vec<&str> {
+        match self {
+            Filter::Tag(name) => vec![name.as_str()],
+            Filter::UsedWithin(_) | Filter::UnusedFor(_) | Filter::NameContains(_) => Vec::new(),
+            Filter::Not(filter) => filter.tag_names(),
+            Filter::And(a, b) | Filter::Or(a, b) => {
+                let mut names = a.tag_names();
+                names.extend(b.tag_names());
+                names
+            }
+        }
+    }
+}
+
+/// `value` as it has to be written after `tag:` or `name:`, in quotes if it has spaces or
+/// parentheses. Values that need quotes but contain one can't be written at all.
+fn quote(value: &str) -> Option<String> {
+    let plain = !value.is_empty()
+        && !value.starts_with('"')
+        && !value.contains(|c: char| c.is_whitespace() || c == '(' || c == ')');
+    if plain {
+        Some(value.to_string())
+    } else if value.contains('"') {
+        None
+    } else {
+        Some(format!("\"{}\"", value))
+    }
+}
+
+/// Makes the `tag:` terms naming a key of `renames` name its value instead, keeping the rest of
+/// the expression as it was written.
+pub(crate) fn rename_tags(
+    expression: &str,
+    renames: &HashMap<String, String>,
+) -> Result<String, String> {
+    let tokens = match tokenize(expression) {
+        Ok(tokens) => tokens,
+        // Such expressions match nothing, whatever the tags are called
+        Err(_) => return Ok(expression.to_string()),
+    };
+
+    let mut renamed = String::new();
+    let mut position = 0;
+    for (token, range) in tokens {
+        let new_name = match &token {
+            Token::Term(key, value) if key == "tag" => renames.get(value),
+            _ => None,
+        };
+        if let Some(new_name) = new_name {
+            let new_value = quote(new_name).ok_or_else(|| {
+                format!("Tag name {} can't be written in an expression", new_name)
+            })?;
+            renamed.push_str(&expression[position..range.start]);
+            renamed.push_str(&new_value);
+            position = range.end;
+        }
+    }
+    renamed.push_str(&expression[position..]);
+
+    Ok(renamed)
+}
+
+pub(crate) type ItemFilter = Box<dyn BoxableExpression<items::table, Pg, SqlType = Bool>>;
+
+/// What a filter gets compared against, loaded once per user.
+pub(crate) struct FilterContext {
+    /// Ids of each tag and all tags below it, by name.
+    pub tags: HashMap<String, Vec<i32>>,
+    pub today: NaiveDate,
+}
+
+impl FilterContext {
+    pub(crate) fn load(c: &PgConnection, uid: i32, today: NaiveDate) -> QueryResult<Self> {
+        let tree = TagTree::load(c, uid)?;
+        let tag_names = tags::table
+            .filter(tags::user_id.eq(uid))
+            .select((tags::id, tags::tag_name))
+            .load::<(i32, String)>(c)?;
+
+        Ok(FilterContext {
+            tags: tag_names
+                .into_iter()
+                .map(|(tid, name)| (name, tree.subtree(tid).into_iter().collect()))
+                .collect(),
+            today,
+        })
+    }
+}
+
+/// The latest day that is no longer within the last `days` days. Like in the neglected items
+/// report, an item used `days` days ago counts as unused for `days` days.
+fn cutoff(days: i64, today: NaiveDate) -> NaiveDate {
+    today - Duration::days(days)
+}
+
+fn used_within(days: i64, today: NaiveDate) -> ItemFilter {
+    Box::new(
+        items::id.eq_any(
+            uses::table
+                .filter(uses::date.gt(cutoff(days, today)))
+                .select(uses::item_id),
+        ),
+    )
+}
+
+/// `ILIKE` pattern for names containing `text`, whose wildcards are matched literally.
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Turns the filter into a condition on `items`. Tags that don't exist (anymore) match nothing.
+fn compile(filter: &Filter, context: &FilterContext) -> ItemFilter {
+    match filter {
+        Filter::Tag(name) => {
+            let tag_ids = context.tags.get(name).cloned().unwrap_or_default();
+            Box::new(
+                items::id.eq_any(
+                    item_tags::table
+                        .filter(item_tags::tag_id.eq_any(tag_ids))
+                        .select(item_tags::item_id),
+                ),
+            )
+        }
+        Filter::UsedWithin(days) => used_within(*days, context.today),
+        Filter::UnusedFor(days) => Box::new(not(used_within(*days, context.today))),
+        Filter::NameContains(text) => Box::new(items::item_name.ilike(like_pattern(text))),
+        Filter::Not(filter) => Box::new(not(compile(filter, context))),
+        Filter::And(a, b) => Box::new(compile(a, context).and(compile(b, context))),
+        Filter::Or(a, b) => Box::new(compile(a, context).or(compile(b, context))),
+    }
+}
+
+/// The condition a smart tag's expression puts on items.
+pub(crate) fn item_filter(expression: &str, context: &FilterContext) -> ItemFilter {
+    match parse(expression) {
+        Ok(filter) => compile(&filter, context),
+        // Expressions are checked when they are saved, so this only happens for old ones the
+        // language no longer accepts
+        Err(_) => Box::new(false.into_sql::<Bool>()),
+    }
+}
+
+/// Ids of the user's items a smart tag's expression selects.
+pub(crate) fn matching_items(
+    c: &PgConnection,
+    uid: i32,
+    expression: &str,
+    context: &FilterContext,
+) -> QueryResult<Vec<i32>> {
+    items::table
+        .filter(items::user_id.eq(uid))
+        .filter(item_filter(expression, context))
+        .select(items::id)
+        .load::<i32>(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str) -> Filter {
+        Filter::Tag(name.to_string())
+    }
+
+    fn not(filter: Filter) -> Filter {
+        Filter::Not(Box::new(filter))
+    }
+
+    fn and(a: Filter, b: Filter) -> Filter {
+        Filter::And(Box::new(a), Box::new(b))
+    }
+
+    fn or(a: Filter, b: Filter) -> Filter {
+        Filter::Or(Box::new(a), Box::new(b))
+    }
+
+    #[test]
+    fn parses_terms() {
+        assert_eq!(parse("tag:summer"), Ok(tag("summer")));
+        assert_eq!(parse("used:7"), Ok(Filter::UsedWithin(7)));
+        assert_eq!(parse("unused:60"), Ok(Filter::UnusedFor(60)));
+        assert_eq!(
+            parse("name:tux"),
+            Ok(Filter::NameContains("tux".to_string()))
+        );
+        assert_eq!(parse("TAG:summer"), Ok(tag("summer")));
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_than_or() {
+        assert_eq!(
+            parse("tag:a OR tag:b AND NOT tag:c"),
+            Ok(or(tag("a"), and(tag("b"), not(tag("c")))))
+        );
+        assert_eq!(
+            parse("NOT tag:a AND tag:b OR tag:c"),
+            Ok(or(and(not(tag("a")), tag("b")), tag("c")))
+        );
+        assert_eq!(
+            parse("tag:a and tag:b and tag:c"),
+            Ok(and(and(tag("a"), tag("b")), tag("c")))
+        );
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(
+            parse("(tag:a OR tag:b) AND NOT (tag:c OR tag:d)"),
+            Ok(and(or(tag("a"), tag("b")), not(or(tag("c"), tag("d")))))
+        );
+        assert_eq!(parse("((tag:a))"), Ok(tag("a")));
+        assert_eq!(
+            parse("(tag:a"),
+            Err("Missing closing parenthesis".to_string())
+        );
+        assert_eq!(
+            parse("tag:a)"),
+            Err("Unexpected closing parenthesis".to_string())
+        );
+    }
+
+    #[test]
+    fn quotes_keep_spaces_and_parentheses() {
+        assert_eq!(parse(r#"tag:"rain coat""#), Ok(tag("rain coat")));
+        assert_eq!(
+            parse(r#"(name:"a (b) AND c")"#),
+            Ok(Filter::NameContains("a (b) AND c".to_string()))
+        );
+        assert_eq!(
+            parse(r#"tag:"" OR tag:a"#),
+            Err("Missing value after tag:".to_string())
+        );
+        assert_eq!(
+            parse(r#"tag:"rain coat"#),
+            Err("Missing closing quote after tag:".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert_eq!(
+            parse(""),
+            Err("Unexpected end of expression, expected a term".to_string())
+        );
+        assert_eq!(
+            parse("tag:a tag:b"),
+            Err("Expected AND or OR between terms".to_string())
+        );
+        assert_eq!(
+            parse("tag:a AND OR tag:b"),
+            Err("Unexpected OR, expected a term".to_string())
+        );
+        assert_eq!(
+            parse("summer"),
+            Err("Unexpected summer, expected a term like tag:name".to_string())
+        );
+        assert_eq!(
+            parse("color:red"),
+            Err("Unknown term color:, expected tag:, used:, unused: or name:".to_string())
+        );
+    }
+
+    #[test]
+    fn day_counts_are_bounded() {
+        assert_eq!(parse("unused:0"), Ok(Filter::UnusedFor(0)));
+        assert_eq!(parse("unused:36500"), Ok(Filter::UnusedFor(MAX_DAYS)));
+        for days in ["36501", "-1", "1.5", "many"] {
+            assert_eq!(
+                parse(&format!("unused:{}", days)),
+                Err(format!(
+                    "Invalid unused:{}, expected a number of days up to 36500",
+                    days
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn used_days_end_today() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        // used:0 matches nothing, used:1 only uses of today
+        assert_eq!(cutoff(0, today), today);
+        assert_eq!(cutoff(1, today), today.pred_opt().unwrap());
+        // An item used 60 days ago is unused:60, but not unused:61
+        let used = NaiveDate::from_ymd_opt(2026, 8, 19).unwrap();
+        assert!(!(used > cutoff(60, today)));
+        assert!(used > cutoff(61, today));
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}tag:a{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(parse(&nested(MAX_DEPTH)), Ok(tag("a")));
+        assert_eq!(
+            parse(&nested(MAX_DEPTH + 1)),
+            Err("Expression is nested too deeply".to_string())
+        );
+
+        let negated = |depth: usize| format!("{}tag:a", "NOT ".repeat(depth));
+        assert!(parse(&negated(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&negated(MAX_DEPTH + 1)),
+            Err("Expression is nested too deeply".to_string())
+        );
+    }
+
+    #[test]
+    fn length_is_limited() {
+        let long = |length: usize| format!("name:{}", "a".repeat(length - "name:".len()));
+        assert!(parse(&long(MAX_LENGTH)).is_ok());
+        assert_eq!(
+            parse(&long(MAX_LENGTH + 1)),
+            Err("Expression is longer than 1000 characters".to_string())
+        );
+    }
+
+    #[test]
+    fn name_wildcards_match_literally() {
+        assert_eq!(like_pattern("tux"), "%tux%");
+        assert_eq!(like_pattern("100%"), "%100\\%%");
+        assert_eq!(like_pattern("a_b"), "%a\\_b%");
+        assert_eq!(like_pattern("a\\%"), "%a\\\\\\%%");
+    }
+
+    #[test]
+    fn lists_tag_names() {
+        let filter = parse("tag:a OR NOT (tag:b AND used:3) OR name:c").unwrap();
+        assert_eq!(filter.tag_names(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn renames_tags_keeping_the_rest() {
+        let renames = HashMap::from([
+            ("rain".to_string(), "rain coat".to_string()),
+            ("tops".to_string(), "Tops".to_string()),
+        ]);
+        assert_eq!(
+            rename_tags("(tag:rain  OR TAG:tops) and name:rain", &renames),
+            Ok(r#"(tag:"rain coat"  OR TAG:Tops) and name:rain"#.to_string())
+        );
+        assert_eq!(
+            rename_tags(r#"tag:"rain" AND NOT tag:snow"#, &renames),
+            Ok(r#"tag:"rain coat" AND NOT tag:snow"#.to_string())
+        );
+
+        let renames = HashMap::from([("rain".to_string(), r#"say "hi""#.to_string())]);
+        assert_eq!(
+            rename_tags("tag:rain", &renames),
+            Err(r#"Tag name say "hi" can't be written in an expression"#.to_string())
+        );
+        assert_eq!(
+            rename_tags("tag:snow", &renames),
+            Ok("tag:snow".to_string())
+        );
+    }
+
+    #[test]
+    fn quotes_only_where_needed() {
+        assert_eq!(quote("rain"), Some("rain".to_string()));
+        assert_eq!(quote("a:b"), Some("a:b".to_string()));
+        assert_eq!(quote(r#"say"hi""#), Some(r#"say"hi""#.to_string()));
+        assert_eq!(quote("rain coat"), Some(r#""rain coat""#.to_string()));
+        assert_eq!(quote("(x)"), Some(r#""(x)""#.to_string()));
+        assert_eq!(quote(r#""x"#), None);
+        assert_eq!(quote(r#"say "hi""#), None);
+    }
+}
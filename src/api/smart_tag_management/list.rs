@@ -0,0 +1,64 @@
+use crate::api::smart_tag_management::filter::{matching_items, FilterContext};
+use crate::api::smart_tag_management::models::SmartTag;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::Local;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct SmartTagOut {
+    pub id: i32,
+    pub smart_tag_name: String,
+    pub expression: String,
+    /// Number of items the expression currently selects.
+    pub item_count: usize,
+}
+
+/// Loads the user's smart tags, or only `smart_tag` if given, sorted by name.
+pub(crate) fn load_smart_tags(
+    c: &PgConnection,
+    uid: i32,
+    smart_tag: Option<i32>,
+) -> QueryResult<Vec<SmartTagOut>> {
+    let smart_tag_list = {
+        use schema::smart_tags::dsl::*;
+        let mut query = smart_tags.filter(user_id.eq(uid)).into_boxed();
+        if let Some(smart_tag) = smart_tag {
+            query = query.filter(id.eq(smart_tag));
+        }
+        query.order((smart_tag_name, id)).load::<SmartTag>(c)
+    }?;
+    let context = FilterContext::load(c, uid, Local::now().date_naive())?;
+
+    smart_tag_list
+        .into_iter()
+        .map(|smart_tag| {
+            Ok(SmartTagOut {
+                item_count: matching_items(c, uid, &smart_tag.expression, &context)?.len(),
+                id: smart_tag.id,
+                smart_tag_name: smart_tag.smart_tag_name,
+                expression: smart_tag.expression,
+            })
+        })
+        .collect()
+}
+
+#[get("/smart_tags")]
+pub(crate) async fn get_smart_tags(
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<Vec<SmartTagOut>>, ErrorResponse> {
+    let out = conn
+        .run(move |c| load_smart_tags(c, user.0.id, None))
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load smart tags".to_string())
+        })?;
+
+    Ok(Json(out))
+}
@@ -0,0 +1,7 @@
+pub(crate) mod create;
+pub(crate) mod delete;
+pub(crate) mod edit;
+pub(crate) mod filter;
+pub(crate) mod list;
+pub(crate) mod models;
+pub(crate) mod references;
@@ -0,0 +1,11 @@
+use crate::schema::smart_tags;
+use std::fmt::Debug;
+
+#[derive(Queryable, Debug, Identifiable, AsChangeset)]
+#[table_name = "smart_tags"]
+pub struct SmartTag {
+    pub id: i32,
+    pub user_id: i32,
+    pub smart_tag_name: String,
+    pub expression: String,
+}
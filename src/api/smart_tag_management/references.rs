@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use crate::api::smart_tag_management::filter::{parse, rename_tags};
+use crate::api::smart_tag_management::models::SmartTag;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+
+// Smart tags refer to tags by name, so whatever renames or removes tags has to keep them in line,
+// in the same transaction.
+
+fn load_smart_tags(c: &PgConnection, uid: i32) -> QueryResult<Vec<SmartTag>> {
+    use schema::smart_tags::dsl::*;
+
+    smart_tags
+        .filter(user_id.eq(uid))
+        .order(id)
+        .for_update()
+        .load::<SmartTag>(c)
+}
+
+/// Rewrites the user's smart tags to call the tags renamed by `renames`, old name to new name, by
+/// their new names.
+pub(crate) fn rename_tag_references(
+    c: &PgConnection,
+    uid: i32,
+    renames: &HashMap<String, String>,
+) -> Result<(), ErrorResponse> {
+    for smart_tag in load_smart_tags(c, uid)? {
+        let renamed = rename_tags(&smart_tag.expression, renames).map_err(|err| {
+            ErrorResponse::new(
+                Status { code: 400 },
+                format!(
+                    "Couldn't update smart tag {}: {}",
+                    smart_tag.smart_tag_name, err
+                ),
+            )
+        })?;
+        if renamed != smart_tag.expression {
+            use schema::smart_tags::dsl::*;
+            diesel::update(smart_tags.filter(id.eq(smart_tag.id)))
+                .set(expression.eq(renamed))
+                .execute(c)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Fails with 409 if any of the user's smart tags refers to the tag `name`.
+pub(crate) fn check_unreferenced(
+    c: &PgConnection,
+    uid: i32,
+    name: &str,
+) -> Result<(), ErrorResponse> {
+    let referring = load_smart_tags(c, uid)?
+        .into_iter()
+        .filter(|smart_tag| {
+            parse(&smart_tag.expression)
+                .map(|filter| filter.tag_names().contains(&name))
+                .unwrap_or(false)
+        })
+        .map(|smart_tag| smart_tag.smart_tag_name)
+        .collect::<Vec<_>>();
+
+    if referring.is_empty() {
+        Ok(())
+    } else {
+        Err(ErrorResponse::new(
+            Status { code: 409 },
+            format!(
+                "Tag {} is used by the smart tags {}",
+                name,
+                referring.join(", ")
+            ),
+        ))
+    }
+}
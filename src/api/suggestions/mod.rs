@@ -0,0 +1,2 @@
+pub(crate) mod strategies;
+pub(crate) mod suggest;
@@ -0,0 +1,82 @@
+use chrono::NaiveDate;
+
+/// Anything that can be suggested, an item or a whole outfit.
+pub(crate) struct Candidate {
+    pub(crate) last_worn: Option<NaiveDate>,
+    pub(crate) wears: i64,
+}
+
+/// Decides in which order candidates are suggested.
+///
+/// New strategies only have to be added to [`STRATEGIES`] to be selectable through the
+/// `strategy` parameter of the suggestions endpoint.
+pub(crate) trait RankingStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Candidates with a higher score are suggested first.
+    fn score(&self, candidate: &Candidate, today: NaiveDate) -> f64;
+
+    /// Short explanation of the score, shown with the suggestion.
+    fn reason(&self, candidate: &Candidate, today: NaiveDate) -> String;
+}
+
+/// Prefers whatever hasn't been worn for the longest time.
+struct LeastRecentlyWorn;
+
+impl RankingStrategy for LeastRecentlyWorn {
+    fn name(&self) -> &'static str {
+        "least_recent"
+    }
+
+    fn score(&self, candidate: &Candidate, today: NaiveDate) -> f64 {
+        match candidate.last_worn {
+            Some(last) => (today - last).num_days() as f64,
+            None => f64::MAX,
+        }
+    }
+
+    fn reason(&self, candidate: &Candidate, today: NaiveDate) -> String {
+        match candidate.last_worn.map(|last| (today - last).num_days()) {
+            Some(0) => "Worn today".to_string(),
+            Some(1) => "Last worn yesterday".to_string(),
+            Some(days) => format!("Not worn for {} days", days),
+            None => "Never worn".to_string(),
+        }
+    }
+}
+
+/// Prefers whatever has been worn the least often overall.
+struct LeastWorn;
+
+impl RankingStrategy for LeastWorn {
+    fn name(&self) -> &'static str {
+        "least_worn"
+    }
+
+    fn score(&self, candidate: &Candidate, _today: NaiveDate) -> f64 {
+        -(candidate.wears as f64)
+    }
+
+    fn reason(&self, candidate: &Candidate, _today: NaiveDate) -> String {
+        match candidate.wears {
+            0 => "Never worn".to_string(),
+            1 => "Worn only once".to_string(),
+            wears => format!("Worn only {} times", wears),
+        }
+    }
+}
+
+pub(crate) const DEFAULT_STRATEGY: &str = "least_recent";
+
+const STRATEGIES: &[&dyn RankingStrategy] = &[&LeastRecentlyWorn, &LeastWorn];
+
+pub(crate) fn strategy(name: &str) -> Option<&'static dyn RankingStrategy> {
+    STRATEGIES
+        .iter()
+        .copied()
+        .find(|strategy| strategy.name() == name)
+}
+
+pub(crate) fn strategy_names() -> Vec<&'static str> {
+    STRATEGIES.iter().map(|strategy| strategy.name()).collect()
+}
@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::api::analytics::queries::load_items;
+use crate::api::item_management::queries::{inventory_counts, last_uses, use_counts};
+use crate::api::item_management::tag_tree::TagTree;
+use crate::api::outfit_management::get_outfit::load_outfits;
+use crate::api::suggestions::strategies::{
+    strategy as find_strategy, strategy_names, Candidate, RankingStrategy, DEFAULT_STRATEGY,
+};
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::Local;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+const DEFAULT_LIMIT: usize = 5;
+
+#[derive(Serialize)]
+pub struct ItemSuggestion {
+    pub item_id: i32,
+    pub item_name: String,
+    /// The category tag the item was picked for, if categories were requested.
+    pub category_tag: Option<i32>,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct OutfitSuggestion {
+    pub outfit_id: i32,
+    pub outfit_name: String,
+    pub items: Vec<i32>,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct Suggestions {
+    pub strategy: &'static str,
+    pub items: Vec<ItemSuggestion>,
+    pub outfits: Vec<OutfitSuggestion>,
+}
+
+struct Constraints {
+    required_tags: Vec<i32>,
+    excluded_tags: Vec<i32>,
+}
+
+impl Constraints {
+    fn allow(&self, tags: &HashSet<i32>) -> bool {
+        self.required_tags.iter().all(|tag| tags.contains(tag))
+            && !self.excluded_tags.iter().any(|tag| tags.contains(tag))
+    }
+}
+
+/// Sorts best first, by name where the strategy can't decide.
+fn rank<T>(candidates: &mut [(f64, String, T)]) {
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+}
+
+/// Proposes items and outfits to wear next that are still in the inventory.
+///
+/// Only candidates with all `required_tags` and none of the `excluded_tags` are suggested, where
+/// an outfit has the tags of all its items. Like in the item list and smart tags, an item tagged
+/// `Shirts` also counts as tagged with `Tops` above it. With `category_tags`, the item suggestions
+/// hold the best item of every category instead of the overall best ones.
+#[get("/suggestions?<required_tags>&<excluded_tags>&<category_tags>&<strategy>&<limit>")]
+pub(crate) async fn suggestions(
+    user: UserLoggedIn,
+    required_tags: Vec<i32>,
+    excluded_tags: Vec<i32>,
+    category_tags: Vec<i32>,
+    strategy: Option<String>,
+    limit: Option<usize>,
+    conn: DbConn,
+) -> Result<Json<Suggestions>, ErrorResponse> {
+    let strategy_name = strategy.as_deref().unwrap_or(DEFAULT_STRATEGY);
+    let strategy: &dyn RankingStrategy = find_strategy(strategy_name).ok_or_else(|| {
+        ErrorResponse::new(
+            Status { code: 400 },
+            format!(
+                "Unknown strategy {}, expected one of {}",
+                strategy_name,
+                strategy_names().join(", ")
+            ),
+        )
+    })?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let constraints = Constraints {
+        required_tags,
+        excluded_tags,
+    };
+
+    let uid = user.0.id;
+    let (item_list, last_used, wears, inventory, item_tags, tree, outfits) = conn
+        .run(move |c| {
+            let item_list = load_items(c, uid)?;
+            let item_ids = item_list.iter().map(|(iid, _)| *iid).collect::<Vec<_>>();
+            let item_tags = {
+                use schema::item_tags;
+                use schema::tags;
+                item_tags::table
+                    .inner_join(tags::table)
+                    .filter(tags::user_id.eq(uid))
+                    .select((item_tags::item_id, item_tags::tag_id))
+                    .load::<(i32, i32)>(c)
+            }?;
+
+            Ok::<_, diesel::result::Error>((
+                item_list,
+                last_uses(c, &item_ids)?,
+                use_counts(c, &item_ids)?,
+                inventory_counts(c, &item_ids)?,
+                item_tags,
+                TagTree::load(c, uid)?,
+                load_outfits(c, uid, None)?,
+            ))
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load items".to_string()))?;
+
+    let mut tags_of = HashMap::<i32, HashSet<i32>>::new();
+    for (iid, tid) in item_tags {
+        let tags = tags_of.entry(iid).or_default();
+        tags.insert(tid);
+        tags.extend(tree.ancestors(tid));
+    }
+    let no_tags = HashSet::new();
+    let owned = |iid: &i32| inventory.get(iid).copied().unwrap_or(0) > 0;
+
+    let today = Local::now().date_naive();
+    let mut items = item_list
+        .into_iter()
+        .filter(|(iid, _)| owned(iid))
+        .filter(|(iid, _)| constraints.allow(tags_of.get(iid).unwrap_or(&no_tags)))
+        .map(|(iid, name)| {
+            let candidate = Candidate {
+                last_worn: last_used.get(&iid).copied(),
+                wears: wears.get(&iid).copied().unwrap_or(0),
+            };
+            (
+                strategy.score(&candidate, today),
+                name,
+                (iid, strategy.reason(&candidate, today)),
+            )
+        })
+        .collect::<Vec<_>>();
+    rank(&mut items);
+
+    let item_suggestions = if category_tags.is_empty() {
+        items
+            .into_iter()
+            .take(limit)
+            .map(|(_, name, (iid, reason))| ItemSuggestion {
+                item_id: iid,
+                item_name: name,
+                category_tag: None,
+                reason,
+            })
+            .collect()
+    } else {
+        let mut picked = Vec::new();
+        for category in category_tags {
+            let best = items.iter().position(|(_, _, (iid, _))| {
+                tags_of
+                    .get(iid)
+                    .is_some_and(|tags| tags.contains(&category))
+            });
+            if let Some(best) = best {
+                let (_, name, (iid, reason)) = items.remove(best);
+                picked.push(ItemSuggestion {
+                    item_id: iid,
+                    item_name: name,
+                    category_tag: Some(category),
+                    reason,
+                });
+            }
+        }
+        picked
+    };
+
+    let mut outfits = outfits
+        .into_iter()
+        .filter(|outfit| !outfit.items.is_empty() && outfit.items.iter().all(owned))
+        .filter(|outfit| {
+            let tags = outfit
+                .items
+                .iter()
+                .filter_map(|iid| tags_of.get(iid))
+                .flatten()
+                .copied()
+                .collect::<HashSet<_>>();
+            constraints.allow(&tags)
+        })
+        .map(|outfit| {
+            // Wearing every item on its own wears the outfit just as well, so it is as fresh as
+            // its least recently used item and worn as often as its least used one
+            let items_worn = outfit
+                .items
+                .iter()
+                .map(|iid| last_used.get(iid).copied())
+                .collect::<Option<Vec<_>>>()
+                .and_then(|dates| dates.into_iter().min());
+            let items_wears = outfit
+                .items
+                .iter()
+                .map(|iid| wears.get(iid).copied().unwrap_or(0))
+                .min()
+                .unwrap_or(0);
+            let candidate = Candidate {
+                last_worn: outfit.last_worn.max(items_worn),
+                wears: i64::from(outfit.wear_count).max(items_wears),
+            };
+            (
+                strategy.score(&candidate, today),
+                outfit.outfit_name,
+                (outfit.id, outfit.items, strategy.reason(&candidate, today)),
+            )
+        })
+        .collect::<Vec<_>>();
+    rank(&mut outfits);
+
+    Ok(Json(Suggestions {
+        strategy: strategy.name(),
+        items: item_suggestions,
+        outfits: outfits
+            .into_iter()
+            .take(limit)
+            .map(|(_, name, (oid, items, reason))| OutfitSuggestion {
+                outfit_id: oid,
+                outfit_name: name,
+                items,
+                reason,
+            })
+            .collect(),
+    }))
+}
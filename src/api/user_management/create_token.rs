@@ -0,0 +1,93 @@
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::api::user_management::tokens::{generate_token, hash_token, SCOPES};
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDateTime;
+use diesel::dsl::{now, IntervalDsl};
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(FromForm)]
+pub struct FormToken {
+    name: String,
+    scopes: Vec<String>,
+    expires_in_days: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct CreatedTokenOut {
+    pub id: i32,
+    pub token_name: String,
+    /// The only time the secret is shown, only its hash is stored.
+    pub token: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// Tokens can only be minted from a browser session, so a leaked token can't create more of them.
+#[post("/tokens", data = "<form_token>")]
+pub(crate) async fn create_token(
+    session: SessionLoggedIn,
+    conn: DbConn,
+    form_token: Form<FormToken>,
+) -> Result<Json<CreatedTokenOut>, ErrorResponse> {
+    let form_token = form_token.into_inner();
+
+    if let Some(scope) = form_token
+        .scopes
+        .iter()
+        .find(|scope| !SCOPES.contains(&scope.as_str()))
+    {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            format!("Unknown scope {}", scope),
+        ));
+    }
+    if matches!(form_token.expires_in_days, Some(days) if days <= 0) {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Expiry must be at least one day".to_string(),
+        ));
+    }
+
+    let secret = generate_token();
+    let hash = hash_token(&secret);
+    let uid = session.user.id;
+
+    let (tid, name, token_scopes, expiry) = conn
+        .run(move |c| {
+            use schema::api_tokens::dsl::*;
+
+            diesel::insert_into(api_tokens)
+                .values((
+                    user_id.eq(uid),
+                    token_name.eq(form_token.name),
+                    token_hash.eq(hash),
+                    scopes.eq(form_token.scopes),
+                    form_token
+                        .expires_in_days
+                        .map(|days| expires_at.eq((now + days.days()).nullable())),
+                ))
+                .returning((id, token_name, scopes, expires_at))
+                .get_result::<(i32, String, Vec<String>, Option<NaiveDateTime>)>(c)
+        })
+        .await
+        .map_err(|err| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                format!("Couldn't create token: {}", err),
+            )
+        })?;
+
+    Ok(Json(CreatedTokenOut {
+        id: tid,
+        token_name: name,
+        token: secret,
+        scopes: token_scopes,
+        expires_at: expiry,
+    }))
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use crate::settings::Settings;
+use diesel::prelude::*;
+use rocket::form::Form;
+use rocket::http::{Cookie, CookieJar, Status};
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+
+#[derive(FromForm)]
+pub struct FormDeleteAccount {
+    /// Has to repeat the account's username.
+    confirm: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct DeletionSummary {
+    pub items: usize,
+    pub uses: usize,
+    pub inventory_movements: usize,
+    pub tags: usize,
+    pub item_tags: usize,
+    pub smart_tags: usize,
+    pub outfits: usize,
+    pub outfit_wears: usize,
+    pub sessions: usize,
+    pub api_tokens: usize,
+    pub identities: usize,
+    pub images: usize,
+}
+
+#[delete("/account", data = "<form_delete>")]
+pub(crate) async fn delete_account(
+    form_delete: Form<FormDeleteAccount>,
+    session: SessionLoggedIn,
+    conn: DbConn,
+    cookies: &CookieJar<'_>,
+    settings: &State<Settings>,
+) -> Result<Json<DeletionSummary>, ErrorResponse> {
+    if form_delete.confirm != session.user.username {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Confirm by repeating your username".to_string(),
+        ));
+    }
+
+    let uid = session.user.id;
+    let (mut summary, item_ids) = conn
+        .run(move |c| {
+            c.build_transaction()
+                .read_write()
+                .run::<_, diesel::result::Error, _>(|| {
+                    let mut summary = DeletionSummary::default();
+
+                    let item_ids = {
+                        use schema::items::dsl::*;
+                        items.filter(user_id.eq(uid)).select(id).load::<i32>(c)
+                    }?;
+                    let tag_ids = {
+                        use schema::tags::dsl::*;
+                        tags.filter(user_id.eq(uid)).select(id).load::<i32>(c)
+                    }?;
+
+                    summary.item_tags = {
+                        use schema::item_tags::dsl::*;
+                        diesel::delete(
+                            item_tags.filter(item_id.eq_any(&item_ids).or(tag_id.eq_any(&tag_ids))),
+                        )
+                        .execute(c)
+                    }?;
+                    summary.uses = {
+                        use schema::uses::dsl::*;
+                        diesel::delete(uses.filter(item_id.eq_any(&item_ids))).execute(c)
+                    }?;
+                    summary.inventory_movements = {
+                        use schema::item_inventory::dsl::*;
+                        diesel::delete(item_inventory.filter(item_id.eq_any(&item_ids))).execute(c)
+                    }?;
+                    let outfit_ids = {
+                        use schema::outfits::dsl::*;
+                        outfits.filter(user_id.eq(uid)).select(id).load::<i32>(c)
+                    }?;
+                    {
+                        use schema::outfit_items::dsl::*;
+                        diesel::delete(
+                            outfit_items.filter(
+                                outfit_id.eq_any(&outfit_ids).or(item_id.eq_any(&item_ids)),
+                            ),
+                        )
+                        .execute(c)
+                    }?;
+                    summary.outfit_wears = {
+                        use schema::outfit_wears::dsl::*;
+                        diesel::delete(outfit_wears.filter(outfit_id.eq_any(&outfit_ids)))
+                            .execute(c)
+                    }?;
+                    summary.outfits = {
+                        use schema::outfits::dsl::*;
+                        diesel::delete(outfits.filter(user_id.eq(uid))).execute(c)
+                    }?;
+                    summary.items = {
+                        use schema::items::dsl::*;
+                        diesel::delete(items.filter(user_id.eq(uid))).execute(c)
+                    }?;
+                    summary.tags = {
+                        use schema::tags::dsl::*;
+                        diesel::delete(tags.filter(user_id.eq(uid))).execute(c)
+                    }?;
+                    summary.smart_tags = {
+                        use schema::smart_tags::dsl::*;
+                        diesel::delete(smart_tags.filter(user_id.eq(uid))).execute(c)
+                    }?;
+                    summary.sessions = {
+                        use schema::sessions::dsl::*;
+                        diesel::delete(sessions.filter(user_id.eq(uid))).execute(c)
+                    }?;
+                    summary.api_tokens = {
+                        use schema::api_tokens::dsl::*;
+                        diesel::delete(api_tokens.filter(user_id.eq(uid))).execute(c)
+                    }?;
+                    summary.identities = {
+                        use schema::user_identities::dsl::*;
+                        diesel::delete(user_identities.filter(user_id.eq(uid))).execute(c)
+                    }?;
+                    {
+                        use schema::users::dsl::*;
+                        diesel::delete(users.filter(id.eq(uid))).execute(c)
+                    }?;
+
+                    Ok((summary, item_ids))
+                })
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                "Couldn't delete database entries".to_string(),
+            )
+        })?;
+
+    // Files can't be part of the transaction, so they go once the data is gone for good
+    summary.images = item_ids
+        .iter()
+        .filter(|iid| {
+            fs::remove_file(Path::new(&settings.image_folder).join(iid.to_string())).is_ok()
+        })
+        .count();
+
+    cookies.remove_private(Cookie::named("session"));
+
+    Ok(Json(summary))
+}
@@ -0,0 +1,98 @@
+use crate::api::user_management::login::NewUser;
+use crate::api::user_management::models::User;
+use crate::api::user_management::providers::VerifiedIdentity;
+use crate::schema;
+use crate::schema::user_identities;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Insertable)]
+#[table_name = "user_identities"]
+pub(super) struct NewIdentity<'a> {
+    pub(super) user_id: i32,
+    pub(super) provider: &'a str,
+    pub(super) subject: &'a str,
+}
+
+#[derive(Queryable, Serialize)]
+pub struct IdentityOut {
+    pub id: i32,
+    pub provider: String,
+    pub subject: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// The user a (provider, subject) pair is linked to, if any.
+pub(super) fn identity_user(
+    c: &PgConnection,
+    identity_provider: &str,
+    identity_subject: &str,
+) -> QueryResult<Option<User>> {
+    use schema::user_identities::dsl::*;
+    use schema::users;
+
+    user_identities
+        .filter(
+            provider
+                .eq(identity_provider)
+                .and(subject.eq(identity_subject)),
+        )
+        .inner_join(users::table.on(users::id.eq(user_id)))
+        .select(users::all_columns)
+        .first::<User>(c)
+        .optional()
+}
+
+/// Creates a user whose primary identity (`users.sub`) is the given one and links it.
+pub(super) fn create_user(
+    c: &PgConnection,
+    new_user: &NewUser,
+    password: Option<String>,
+    identity_provider: &str,
+    identity_subject: &str,
+) -> QueryResult<User> {
+    c.transaction(|| {
+        let user = {
+            use schema::users::dsl::*;
+            diesel::insert_into(users)
+                .values((new_user, password_hash.eq(password)))
+                .get_result::<User>(c)
+        }?;
+
+        diesel::insert_into(schema::user_identities::table)
+            .values(&NewIdentity {
+                user_id: user.id,
+                provider: identity_provider,
+                subject: identity_subject,
+            })
+            .execute(c)?;
+
+        Ok(user)
+    })
+}
+
+/// Signs in through a linked identity, creating a new user for identities seen for the first time.
+///
+/// Profile data is only refreshed from the user's primary identity, so logging in through a
+/// secondary account doesn't overwrite the name and email.
+pub(super) fn find_or_create_user(
+    c: &PgConnection,
+    identity: VerifiedIdentity,
+) -> QueryResult<User> {
+    let new_user = NewUser {
+        sub: identity.qualified_sub(),
+        email: identity.email,
+        username: identity.name,
+    };
+
+    match identity_user(c, &identity.provider, &identity.subject)? {
+        Some(user) if user.sub == new_user.sub => {
+            diesel::update(schema::users::table.find(user.id))
+                .set(&new_user)
+                .get_result::<User>(c)
+        }
+        Some(user) => Ok(user),
+        None => create_user(c, &new_user, None, &identity.provider, &identity.subject),
+    }
+}
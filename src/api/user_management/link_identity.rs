@@ -0,0 +1,55 @@
+use crate::api::user_management::identities::{identity_user, NewIdentity};
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::api::user_management::providers::IdentityProviders;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::State;
+
+/// Links another account of an identity provider, verified the same way as in `login`.
+#[post("/identities/link?<provider>", data = "<token>")]
+pub(crate) async fn link_identity(
+    token: String,
+    provider: String,
+    providers: &State<IdentityProviders>,
+    session: SessionLoggedIn,
+    conn: DbConn,
+) -> Result<&'static str, ErrorResponse> {
+    let identity = providers
+        .get(&provider)
+        .ok_or_else(|| {
+            ErrorResponse::new(
+                Status { code: 404 },
+                format!("Unknown identity provider {}", provider),
+            )
+        })?
+        .verify(&token)
+        .await?;
+
+    let uid = session.user.id;
+    conn.run(move |c| {
+        c.transaction::<_, ErrorResponse, _>(|| {
+            match identity_user(c, &identity.provider, &identity.subject)? {
+                Some(user) if user.id == uid => Ok(()),
+                Some(_) => Err(ErrorResponse::new(
+                    Status { code: 409 },
+                    "Identity is already linked to another account".to_string(),
+                )),
+                None => diesel::insert_into(schema::user_identities::table)
+                    .values(&NewIdentity {
+                        user_id: uid,
+                        provider: &identity.provider,
+                        subject: &identity.subject,
+                    })
+                    .execute(c)
+                    .map(|_| ())
+                    .map_err(ErrorResponse::from),
+            }
+        })
+    })
+    .await?;
+
+    Ok("Success")
+}
@@ -0,0 +1,31 @@
+use crate::api::user_management::identities::IdentityOut;
+use crate::api::user_management::models::UserLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+#[get("/identities")]
+pub(crate) async fn list_identities(
+    user: UserLoggedIn,
+    conn: DbConn,
+) -> Result<Json<Vec<IdentityOut>>, ErrorResponse> {
+    let out = conn
+        .run(move |c| {
+            use schema::user_identities::dsl::*;
+
+            user_identities
+                .filter(user_id.eq(user.0.id))
+                .order(created_at.asc())
+                .select((id, provider, subject, created_at))
+                .load::<IdentityOut>(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load identities".to_string())
+        })?;
+
+    Ok(Json(out))
+}
@@ -0,0 +1,57 @@
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::api::user_management::sessions::SESSION_MAX_AGE_DAYS;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDateTime;
+use diesel::dsl::{now, IntervalDsl};
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct SessionOut {
+    pub id: i32,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_seen: NaiveDateTime,
+    pub current: bool,
+}
+
+#[get("/sessions")]
+pub(crate) async fn list_sessions(
+    session: SessionLoggedIn,
+    conn: DbConn,
+) -> Result<Json<Vec<SessionOut>>, ErrorResponse> {
+    let uid = session.user.id;
+
+    let session_list = conn
+        .run(move |c| {
+            use schema::sessions::dsl::*;
+
+            sessions
+                .filter(user_id.eq(uid))
+                .filter(created_at.gt(now - SESSION_MAX_AGE_DAYS.days()))
+                .order(last_seen.desc())
+                .select((id, user_agent, created_at, last_seen))
+                .load::<(i32, Option<String>, NaiveDateTime, NaiveDateTime)>(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load sessions".to_string())
+        })?;
+
+    let out = session_list
+        .into_iter()
+        .map(|(id, user_agent, created_at, last_seen)| SessionOut {
+            id,
+            user_agent,
+            created_at,
+            last_seen,
+            current: id == session.session_id,
+        })
+        .collect();
+
+    Ok(Json(out))
+}
@@ -0,0 +1,42 @@
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Queryable, Serialize)]
+pub struct TokenOut {
+    pub id: i32,
+    pub token_name: String,
+    pub scopes: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used: Option<NaiveDateTime>,
+}
+
+#[get("/tokens")]
+pub(crate) async fn list_tokens(
+    session: SessionLoggedIn,
+    conn: DbConn,
+) -> Result<Json<Vec<TokenOut>>, ErrorResponse> {
+    let out = conn
+        .run(move |c| {
+            use schema::api_tokens::dsl::*;
+
+            api_tokens
+                .filter(user_id.eq(session.user.id))
+                .order(created_at.desc())
+                .select((id, token_name, scopes, created_at, expires_at, last_used))
+                .load::<TokenOut>(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't load tokens".to_string())
+        })?;
+
+    Ok(Json(out))
+}
@@ -0,0 +1,386 @@
+use crate::api::user_management::identities::{create_user, identity_user, NewIdentity};
+use crate::api::user_management::login::NewUser;
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::api::user_management::sessions::{start_session, UserAgent};
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use crate::settings::Settings;
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use rand::thread_rng;
+use rocket::form::Form;
+use rocket::http::{CookieJar, Status};
+use rocket::tokio::task::spawn_blocking;
+use rocket::{Build, Rocket, State};
+use std::sync::OnceLock;
+
+/// Identity provider of accounts with a password on this server.
+pub(crate) const LOCAL_PROVIDER: &str = "local";
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(FromForm)]
+pub struct FormRegister {
+    username: String,
+    password: String,
+    email: Option<String>,
+}
+
+#[derive(FromForm)]
+pub struct FormLogin {
+    username: String,
+    password: String,
+}
+
+#[derive(FromForm)]
+pub struct FormLink {
+    username: String,
+    password: String,
+}
+
+#[derive(FromForm)]
+pub struct FormChangePassword {
+    current_password: String,
+    new_password: String,
+}
+
+fn local_sub(username: &str) -> String {
+    format!("{}:{}", LOCAL_PROVIDER, username)
+}
+
+fn check_enabled(settings: &Settings) -> Result<(), ErrorResponse> {
+    if settings.local_accounts {
+        Ok(())
+    } else {
+        Err(ErrorResponse::new(
+            Status { code: 404 },
+            "Local accounts are disabled".to_string(),
+        ))
+    }
+}
+
+fn check_password_length(password: &str) -> Result<(), ErrorResponse> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            format!(
+                "Password must be at least {} characters",
+                MIN_PASSWORD_LENGTH
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Argon2 is deliberately slow, so hashing runs on the blocking thread pool.
+async fn hash_password(password: String) -> Result<String, ErrorResponse> {
+    spawn_blocking(move || {
+        let salt = SaltString::generate(&mut thread_rng());
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await
+    .ok()
+    .and_then(Result::ok)
+    .ok_or_else(|| ErrorResponse::new(Status { code: 500 }, "Couldn't hash password".to_string()))
+}
+
+async fn verify_password(password: String, hash: String) -> bool {
+    spawn_blocking(move || verify_blocking(&password, &hash))
+        .await
+        .unwrap_or(false)
+}
+
+fn verify_blocking(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Checks `password` against a throwaway hash, taking as long as a real check. Logins to unknown
+/// usernames go through this, so their response time doesn't tell which usernames exist.
+async fn reject_password(password: String) {
+    static DUMMY_HASH: OnceLock<Option<String>> = OnceLock::new();
+
+    spawn_blocking(move || {
+        let hash = DUMMY_HASH.get_or_init(|| {
+            let salt = SaltString::generate(&mut thread_rng());
+            Argon2::default()
+                .hash_password(b"not a password of any account", &salt)
+                .map(|hash| hash.to_string())
+                .ok()
+        });
+        if let Some(hash) = hash {
+            verify_blocking(&password, hash);
+        }
+    })
+    .await
+    .ok();
+}
+
+/// Creates the account from `INITIAL_ACCOUNT_USERNAME` and `INITIAL_ACCOUNT_PASSWORD` on startup,
+/// so servers with registration disabled aren't locked out. An existing account is left as is.
+pub(crate) async fn create_initial_account(rocket: Rocket<Build>) -> Rocket<Build> {
+    let settings = rocket.state::<Settings>().expect("settings are managed");
+    let non_empty = |value: &Option<String>| value.clone().filter(|value| !value.is_empty());
+    let name = match non_empty(&settings.initial_account_username) {
+        Some(name) if settings.local_accounts => name.trim().to_string(),
+        _ => return rocket,
+    };
+    let password = non_empty(&settings.initial_account_password)
+        .expect("INITIAL_ACCOUNT_PASSWORD is required when INITIAL_ACCOUNT_USERNAME is set");
+    if check_password_length(&password).is_err() {
+        panic!(
+            "INITIAL_ACCOUNT_PASSWORD must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        );
+    }
+
+    let conn = DbConn::get_one(&rocket).await.expect("database connection");
+    let lookup_name = name.clone();
+    let existing = conn
+        .run(move |c| identity_user(c, LOCAL_PROVIDER, &lookup_name))
+        .await
+        .expect("can load initial account");
+    if existing.is_none() {
+        let hash = hash_password(password)
+            .await
+            .expect("can hash initial account password");
+        let new_user = NewUser {
+            sub: local_sub(&name),
+            username: name,
+            email: String::new(),
+        };
+        conn.run(move |c| {
+            create_user(c, &new_user, Some(hash), LOCAL_PROVIDER, &new_user.username)
+        })
+        .await
+        .expect("can create initial account");
+    }
+
+    rocket
+}
+
+#[post("/local/register", data = "<form_register>")]
+pub(crate) async fn register(
+    form_register: Form<FormRegister>,
+    user_agent: UserAgent,
+    conn: DbConn,
+    cookies: &CookieJar<'_>,
+    settings: &State<Settings>,
+) -> Result<&'static str, ErrorResponse> {
+    check_enabled(settings)?;
+    if !settings.registration_enabled {
+        return Err(ErrorResponse::new(
+            Status { code: 403 },
+            "Registration is disabled".to_string(),
+        ));
+    }
+
+    let form_register = form_register.into_inner();
+    let name = form_register.username.trim().to_string();
+    if name.is_empty() {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Username must not be empty".to_string(),
+        ));
+    }
+    check_password_length(&form_register.password)?;
+
+    let hash = hash_password(form_register.password).await?;
+    let new_user = NewUser {
+        sub: local_sub(&name),
+        username: name,
+        email: form_register.email.unwrap_or_default(),
+    };
+
+    let user = conn
+        .run(move |c| create_user(c, &new_user, Some(hash), LOCAL_PROVIDER, &new_user.username))
+        .await
+        .map_err(|err| match err {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ErrorResponse::new(Status { code: 409 }, "Username already taken".to_string())
+            }
+            _ => ErrorResponse::new(Status { code: 500 }, "Couldn't create user".to_string()),
+        })?;
+
+    start_session(&conn, cookies, user.id, user_agent).await?;
+
+    Ok("Success")
+}
+
+#[post("/local/login", data = "<form_login>")]
+pub(crate) async fn local_login(
+    form_login: Form<FormLogin>,
+    user_agent: UserAgent,
+    conn: DbConn,
+    cookies: &CookieJar<'_>,
+    settings: &State<Settings>,
+) -> Result<&'static str, ErrorResponse> {
+    check_enabled(settings)?;
+
+    let form_login = form_login.into_inner();
+    let name = form_login.username.trim().to_string();
+
+    let user = conn
+        .run(move |c| identity_user(c, LOCAL_PROVIDER, &name))
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load user".to_string()))?;
+
+    let invalid = || {
+        ErrorResponse::new(
+            Status { code: 401 },
+            "Invalid username or password".to_string(),
+        )
+    };
+
+    let Some((user, hash)) =
+        user.and_then(|user| user.password_hash.clone().map(|hash| (user, hash)))
+    else {
+        reject_password(form_login.password).await;
+        return Err(invalid());
+    };
+
+    if !verify_password(form_login.password, hash).await {
+        return Err(invalid());
+    }
+
+    start_session(&conn, cookies, user.id, user_agent).await?;
+
+    Ok("Success")
+}
+
+/// Adds a username and password to an account that signs in through another provider.
+#[post("/local/link", data = "<form_link>")]
+pub(crate) async fn link_local(
+    form_link: Form<FormLink>,
+    session: SessionLoggedIn,
+    conn: DbConn,
+    settings: &State<Settings>,
+) -> Result<&'static str, ErrorResponse> {
+    check_enabled(settings)?;
+
+    let form_link = form_link.into_inner();
+    let name = form_link.username.trim().to_string();
+    if name.is_empty() {
+        return Err(ErrorResponse::new(
+            Status { code: 400 },
+            "Username must not be empty".to_string(),
+        ));
+    }
+    check_password_length(&form_link.password)?;
+
+    let hash = hash_password(form_link.password).await?;
+    let uid = session.user.id;
+
+    conn.run(move |c| {
+        c.transaction::<_, ErrorResponse, _>(|| {
+            use schema::users::dsl::*;
+
+            // There is one password per user, so there can only be one local identity as well
+            let updated = diesel::update(users.find(uid).filter(password_hash.is_null()))
+                .set(password_hash.eq(hash))
+                .execute(c)?;
+            if updated == 0 {
+                return Err(ErrorResponse::new(
+                    Status { code: 409 },
+                    "Account already has a local login".to_string(),
+                ));
+            }
+
+            diesel::insert_into(schema::user_identities::table)
+                .values(&NewIdentity {
+                    user_id: uid,
+                    provider: LOCAL_PROVIDER,
+                    subject: &name,
+                })
+                .execute(c)
+                .map_err(|err| match err {
+                    DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                        ErrorResponse::new(
+                            Status { code: 409 },
+                            "Username already taken".to_string(),
+                        )
+                    }
+                    err => ErrorResponse::from(err),
+                })?;
+
+            Ok(())
+        })
+    })
+    .await?;
+
+    Ok("Success")
+}
+
+/// Changing the password also ends every other session of the user.
+#[post("/local/change_password", data = "<form_password>")]
+pub(crate) async fn change_password(
+    form_password: Form<FormChangePassword>,
+    session: SessionLoggedIn,
+    conn: DbConn,
+    settings: &State<Settings>,
+) -> Result<&'static str, ErrorResponse> {
+    check_enabled(settings)?;
+
+    let form_password = form_password.into_inner();
+    check_password_length(&form_password.new_password)?;
+
+    let uid = session.user.id;
+    let current_hash = conn
+        .run(move |c| {
+            use schema::users::dsl::*;
+
+            users
+                .find(uid)
+                .select(password_hash)
+                .first::<Option<String>>(c)
+        })
+        .await
+        .map_err(|_| ErrorResponse::new(Status { code: 500 }, "Couldn't load user".to_string()))?
+        .ok_or_else(|| {
+            ErrorResponse::new(Status { code: 400 }, "Account has no password".to_string())
+        })?;
+
+    if !verify_password(form_password.current_password, current_hash).await {
+        return Err(ErrorResponse::new(
+            Status { code: 401 },
+            "Current password is wrong".to_string(),
+        ));
+    }
+
+    let hash = hash_password(form_password.new_password).await?;
+    let sid = session.session_id;
+
+    conn.run(move |c| {
+        c.transaction::<_, diesel::result::Error, _>(|| {
+            {
+                use schema::users::dsl::*;
+                diesel::update(users.find(uid))
+                    .set(password_hash.eq(hash))
+                    .execute(c)
+            }?;
+            {
+                use schema::sessions::dsl::*;
+                diesel::delete(sessions.filter(user_id.eq(uid).and(id.ne(sid)))).execute(c)
+            }?;
+
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|_| {
+        ErrorResponse::new(Status { code: 500 }, "Couldn't update password".to_string())
+    })?;
+
+    Ok("Success")
+}
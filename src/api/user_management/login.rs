@@ -1,21 +1,14 @@
-use std::fmt::Debug;
-use std::time::SystemTime;
-
-use crate::api::user_management::models::{User, UserLoggedIn, UserOut};
-use crate::api::user_management::sessions::UserSession;
+use crate::api::user_management::identities::find_or_create_user;
+use crate::api::user_management::models::{UserLoggedIn, UserOut};
+use crate::api::user_management::providers::{IdentityProviders, DEFAULT_PROVIDER};
+use crate::api::user_management::sessions::{start_session, UserAgent};
 use crate::db::DbConn;
 use crate::error::ErrorResponse;
-use crate::schema;
 use crate::schema::users;
-use crate::settings::Settings;
-use diesel::prelude::*;
-use rand::distributions::Alphanumeric;
-use rand::{thread_rng, Rng};
 use rocket::http::CookieJar;
-use rocket::http::{Cookie, Status};
+use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::State;
-use serde::{Deserialize, Serialize};
 
 #[derive(Insertable, AsChangeset)]
 #[table_name = "users"]
@@ -25,19 +18,6 @@ pub struct NewUser {
     pub email: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct TokenClaims {
-    pub sub: String,
-    pub email: String,
-    pub name: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub(super) struct SessionCookie {
-    pub(super) session_key: String,
-    pub(super) creation_time: SystemTime,
-}
-
 #[get("/check_login")]
 pub(crate) async fn check_login(user: UserLoggedIn) -> Json<UserOut> {
     Json(user.0)
@@ -48,79 +28,40 @@ pub(crate) async fn check_login_unauthorised() -> ErrorResponse {
     ErrorResponse::new(Status { code: 401 }, "Login required".to_string())
 }
 
-#[post("/login", data = "<token>")]
+#[get("/login/providers")]
+pub(crate) async fn login_providers(providers: &State<IdentityProviders>) -> Json<Vec<String>> {
+    Json(providers.names())
+}
+
+#[post("/login?<provider>", data = "<token>")]
 pub(crate) async fn login(
     token: String,
-    tokens: &State<UserSession>,
+    provider: Option<String>,
+    providers: &State<IdentityProviders>,
+    user_agent: UserAgent,
     conn: DbConn,
     cookies: &CookieJar<'_>,
-    settings: &State<Settings>,
 ) -> Result<&'static str, ErrorResponse> {
-    let parser = jsonwebtoken_google::Parser::new(&settings.google_client_id);
-    let claims = parser.parse::<TokenClaims>(&token).await.map_err(|_| {
-        ErrorResponse::new(
-            Status { code: 500 },
-            "Couldn't validate Google account".to_string(),
-        )
-    })?;
-
-    let new_user = NewUser {
-        sub: claims.sub.clone(),
-        email: claims.email,
-        username: claims.name,
-    };
-
-    use schema::users::dsl::*;
-
-    conn.run(move |c| {
-        diesel::insert_into(users)
-            .values(&new_user)
-            .on_conflict(sub)
-            .do_update()
-            .set(&new_user)
-            .get_result::<User>(c)
-            .map_err(|_| {
-                ErrorResponse::new(Status { code: 500 }, "Couldn't update user".to_string())
-            })
-    })
-    .await?;
-
-    let session_key = generate_session_key();
-
-    tokens
-        .sessions
-        .lock()
-        .map_err(|_| {
+    let provider_name = provider.as_deref().unwrap_or(DEFAULT_PROVIDER);
+    let identity = providers
+        .get(provider_name)
+        .ok_or_else(|| {
             ErrorResponse::new(
-                Status { code: 500 },
-                "Couldn't update user session".to_string(),
+                Status { code: 404 },
+                format!("Unknown identity provider {}", provider_name),
             )
         })?
-        .insert(session_key.clone(), claims.sub);
+        .verify(&token)
+        .await?;
 
-    let cookie = SessionCookie {
-        session_key,
-        creation_time: SystemTime::now(),
-    };
-
-    let cookie_string = serde_json::to_string(&cookie).map_err(|err| {
-        ErrorResponse::new(
-            Status { code: 500 },
-            format!("Couldn't create session cookie {}", err),
-        )
-    })?;
+    let user = conn
+        .run(move |c| find_or_create_user(c, identity))
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't update user".to_string())
+        })?;
 
-    cookies.add_private(Cookie::new("session", cookie_string));
+    start_session(&conn, cookies, user.id, user_agent).await?;
 
     Ok("Success")
 }
-
-fn generate_session_key() -> String {
-    const LEN: usize = 32;
-
-    thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(LEN)
-        .map(char::from)
-        .collect()
-}
@@ -0,0 +1,29 @@
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::{Cookie, CookieJar, Status};
+
+#[post("/logout")]
+pub(crate) async fn logout(
+    session: Option<SessionLoggedIn>,
+    conn: DbConn,
+    cookies: &CookieJar<'_>,
+) -> Result<&'static str, ErrorResponse> {
+    cookies.remove_private(Cookie::named("session"));
+
+    if let Some(session) = session {
+        conn.run(move |c| {
+            use schema::sessions::dsl::*;
+
+            diesel::delete(sessions.filter(id.eq(session.session_id))).execute(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't end session".to_string())
+        })?;
+    }
+
+    Ok("Success")
+}
@@ -1,3 +1,17 @@
+pub(crate) mod create_token;
+pub(crate) mod delete_account;
+pub(crate) mod identities;
+pub(crate) mod link_identity;
+pub(crate) mod list_identities;
+pub(crate) mod list_sessions;
+pub(crate) mod list_tokens;
+pub(crate) mod local_accounts;
 pub(crate) mod login;
+pub(crate) mod logout;
 pub(crate) mod models;
+pub(crate) mod providers;
+pub(crate) mod revoke_session;
+pub(crate) mod revoke_token;
 pub(crate) mod sessions;
+pub(crate) mod tokens;
+pub(crate) mod unlink_identity;
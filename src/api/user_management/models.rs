@@ -7,6 +7,7 @@ pub struct User {
     pub sub: String,
     pub username: String,
     pub email: String,
+    pub password_hash: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -18,3 +19,12 @@ pub struct UserOut {
 }
 
 pub(crate) struct UserLoggedIn(pub(crate) UserOut);
+
+/// A user authenticated through a browser session, together with the id of that session.
+pub(crate) struct SessionLoggedIn {
+    pub(crate) session_id: i32,
+    pub(crate) user: UserOut,
+}
+
+/// A user authenticated through a personal access token.
+pub(crate) struct TokenLoggedIn(pub(crate) UserOut);
@@ -0,0 +1,56 @@
+use jsonwebtoken_google::{Parser, ParserError};
+use rocket::http::Status;
+use serde::Deserialize;
+
+use super::{IdentityProvider, VerifiedIdentity};
+use crate::error::ErrorResponse;
+
+#[derive(Debug, Deserialize)]
+struct TokenClaims {
+    pub sub: String,
+    pub email: String,
+    pub name: String,
+}
+
+pub(super) struct GoogleProvider {
+    parser: Parser,
+}
+
+impl GoogleProvider {
+    pub(super) fn new(client_id: &str) -> GoogleProvider {
+        GoogleProvider {
+            parser: Parser::new(client_id),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl IdentityProvider for GoogleProvider {
+    fn name(&self) -> &str {
+        "google"
+    }
+
+    async fn verify(&self, token: &str) -> Result<VerifiedIdentity, ErrorResponse> {
+        let claims = self
+            .parser
+            .parse::<TokenClaims>(token)
+            .await
+            .map_err(|err| match err {
+                ParserError::KeyProvider(_) => ErrorResponse::new(
+                    Status { code: 500 },
+                    "Couldn't fetch Google certificates".to_string(),
+                ),
+                _ => ErrorResponse::new(
+                    Status { code: 401 },
+                    "Couldn't validate Google account".to_string(),
+                ),
+            })?;
+
+        Ok(VerifiedIdentity {
+            provider: self.name().to_string(),
+            subject: claims.sub,
+            email: claims.email,
+            name: claims.name,
+        })
+    }
+}
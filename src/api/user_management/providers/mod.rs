@@ -0,0 +1,94 @@
+use crate::error::ErrorResponse;
+use crate::settings::Settings;
+
+mod dev;
+mod google;
+mod oidc;
+
+use dev::{DevProvider, DEV_PROFILE};
+use google::GoogleProvider;
+use oidc::OidcProvider;
+
+/// Provider used by clients that log in without naming one.
+pub(crate) const DEFAULT_PROVIDER: &str = "google";
+
+/// Someone whose ID token was accepted by an identity provider.
+pub(crate) struct VerifiedIdentity {
+    pub(crate) provider: String,
+    pub(crate) subject: String,
+    pub(crate) email: String,
+    pub(crate) name: String,
+}
+
+impl VerifiedIdentity {
+    /// `sub` values are only unique per issuer, so users are keyed by `<provider>:<sub>`.
+    pub(crate) fn qualified_sub(&self) -> String {
+        format!("{}:{}", self.provider, self.subject)
+    }
+}
+
+#[rocket::async_trait]
+pub(crate) trait IdentityProvider: Send + Sync {
+    /// Name clients select the provider by, also used to qualify `users.sub`.
+    fn name(&self) -> &str;
+
+    async fn verify(&self, token: &str) -> Result<VerifiedIdentity, ErrorResponse>;
+}
+
+pub(crate) struct IdentityProviders {
+    providers: Vec<Box<dyn IdentityProvider>>,
+}
+
+impl IdentityProviders {
+    pub(crate) fn new(settings: &Settings) -> IdentityProviders {
+        let mut providers: Vec<Box<dyn IdentityProvider>> = Vec::new();
+
+        if let Some(client_id) = non_empty(&settings.google_client_id) {
+            providers.push(Box::new(GoogleProvider::new(client_id)));
+        }
+
+        if let Some(issuer) = non_empty(&settings.oidc_issuer) {
+            let audience = non_empty(&settings.oidc_audience)
+                .expect("OIDC_AUDIENCE is required when OIDC_ISSUER is set");
+
+            providers.push(Box::new(OidcProvider::new(
+                non_empty(&settings.oidc_provider_name).unwrap_or("oidc"),
+                issuer,
+                audience,
+                non_empty(&settings.oidc_jwks_url),
+            )));
+        }
+
+        if let Some(secret) = non_empty(&settings.dev_auth_secret) {
+            let profile = rocket::Config::figment().profile().to_string();
+            if profile != DEV_PROFILE {
+                panic!(
+                    "DEV_AUTH_SECRET is set, but the dev identity provider requires ROCKET_PROFILE={} (active profile: {})",
+                    DEV_PROFILE, profile
+                );
+            }
+
+            providers.push(Box::new(DevProvider::new(secret)));
+        }
+
+        IdentityProviders { providers }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&dyn IdentityProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name() == name)
+            .map(|provider| provider.as_ref())
+    }
+
+    pub(crate) fn names(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|provider| provider.name().to_string())
+            .collect()
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|value| !value.is_empty())
+}
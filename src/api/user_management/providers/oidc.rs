@@ -0,0 +1,209 @@
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use rocket::http::Status;
+use rocket::tokio::sync::RwLock;
+use serde::Deserialize;
+
+use super::{IdentityProvider, VerifiedIdentity};
+use crate::error::ErrorResponse;
+
+#[derive(Debug, Deserialize)]
+struct TokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Discovery {
+    jwks_uri: String,
+}
+
+/// Tokens with unknown key ids refresh the keys at most this often, so they can't make us flood
+/// the issuer with requests.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+const RSA_ALGORITHMS: [Algorithm; 6] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+];
+
+struct Keys {
+    set: JwkSet,
+    fetched_at: Option<Instant>,
+}
+
+/// Any OpenID Connect issuer, verified against the keys it publishes.
+pub(super) struct OidcProvider {
+    name: String,
+    issuer: String,
+    audience: String,
+    jwks_url: Option<String>,
+    client: reqwest::Client,
+    keys: RwLock<Keys>,
+}
+
+impl OidcProvider {
+    pub(super) fn new(
+        name: &str,
+        issuer: &str,
+        audience: &str,
+        jwks_url: Option<&str>,
+    ) -> OidcProvider {
+        OidcProvider {
+            name: name.to_string(),
+            issuer: issuer.to_string(),
+            audience: audience.to_string(),
+            jwks_url: jwks_url.map(str::to_string),
+            client: reqwest::Client::new(),
+            keys: RwLock::new(Keys {
+                set: JwkSet { keys: Vec::new() },
+                fetched_at: None,
+            }),
+        }
+    }
+
+    async fn fetch_keys(&self) -> Result<JwkSet, reqwest::Error> {
+        let jwks_url = match &self.jwks_url {
+            Some(url) => url.clone(),
+            None => {
+                let discovery_url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.issuer.trim_end_matches('/')
+                );
+                self.client
+                    .get(discovery_url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<Discovery>()
+                    .await?
+                    .jwks_uri
+            }
+        };
+
+        self.client
+            .get(jwks_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await
+    }
+
+    /// Looks up the key for `kid`, refreshing the cached set in case the issuer rotated keys.
+    async fn find_key(&self, kid: &str) -> Result<Jwk, ErrorResponse> {
+        if let Some(jwk) = self.keys.read().await.set.find(kid) {
+            return Ok(jwk.clone());
+        }
+
+        let unknown = || {
+            ErrorResponse::new(
+                Status { code: 401 },
+                format!("Unknown {} signing key", self.name),
+            )
+        };
+
+        // Holding the write lock makes concurrent requests wait for one refresh
+        let mut keys = self.keys.write().await;
+        if let Some(jwk) = keys.set.find(kid) {
+            return Ok(jwk.clone());
+        }
+        let recently_fetched =
+            matches!(keys.fetched_at, Some(at) if at.elapsed() < MIN_REFRESH_INTERVAL);
+        if recently_fetched {
+            return Err(unknown());
+        }
+
+        let set = self.fetch_keys().await.map_err(|err| {
+            ErrorResponse::new(
+                Status { code: 500 },
+                format!("Couldn't fetch {} keys: {}", self.name, err),
+            )
+        })?;
+        *keys = Keys {
+            set,
+            fetched_at: Some(Instant::now()),
+        };
+
+        keys.set.find(kid).cloned().ok_or_else(unknown)
+    }
+
+    /// The key for `kid` and the algorithm it signs with. The algorithm comes from the key, never
+    /// from the token, which could otherwise pick one the key isn't meant for.
+    async fn decoding_key(&self, kid: &str) -> Result<(DecodingKey, Algorithm), ErrorResponse> {
+        let jwk = self.find_key(kid).await?;
+        let invalid_key = || {
+            ErrorResponse::new(
+                Status { code: 500 },
+                format!("Invalid {} signing key", self.name),
+            )
+        };
+
+        match &jwk.algorithm {
+            AlgorithmParameters::RSA(rsa) => {
+                let algorithm = jwk.common.algorithm.unwrap_or(Algorithm::RS256);
+                if !RSA_ALGORITHMS.contains(&algorithm) {
+                    return Err(invalid_key());
+                }
+                let key =
+                    DecodingKey::from_rsa_components(&rsa.n, &rsa.e).map_err(|_| invalid_key())?;
+                Ok((key, algorithm))
+            }
+            _ => Err(ErrorResponse::new(
+                Status { code: 500 },
+                format!("Unsupported {} signing key type", self.name),
+            )),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl IdentityProvider for OidcProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn verify(&self, token: &str) -> Result<VerifiedIdentity, ErrorResponse> {
+        let invalid = || {
+            ErrorResponse::new(
+                Status { code: 401 },
+                format!("Couldn't validate {} account", self.name),
+            )
+        };
+
+        let header = decode_header(token).map_err(|_| invalid())?;
+        let kid = header.kid.ok_or_else(invalid)?;
+        let (key, algorithm) = self.decoding_key(&kid).await?;
+        if header.alg != algorithm {
+            return Err(invalid());
+        }
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+
+        let claims = decode::<TokenClaims>(token, &key, &validation)
+            .map_err(|_| invalid())?
+            .claims;
+
+        let name = claims
+            .name
+            .or(claims.preferred_username)
+            .unwrap_or_else(|| claims.sub.clone());
+
+        Ok(VerifiedIdentity {
+            provider: self.name.clone(),
+            subject: claims.sub,
+            email: claims.email.unwrap_or_default(),
+            name,
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::{Cookie, CookieJar, Status};
+
+#[delete("/sessions/<sid>")]
+pub(crate) async fn revoke_session(
+    session: SessionLoggedIn,
+    sid: i32,
+    conn: DbConn,
+    cookies: &CookieJar<'_>,
+) -> Result<(), ErrorResponse> {
+    let uid = session.user.id;
+
+    let deleted = conn
+        .run(move |c| {
+            use schema::sessions::dsl::*;
+
+            diesel::delete(sessions.filter(id.eq(sid).and(user_id.eq(uid)))).execute(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't revoke session".to_string())
+        })?;
+
+    if deleted == 0 {
+        return Err(ErrorResponse::new(
+            Status { code: 404 },
+            "Couldn't find session".to_string(),
+        ));
+    }
+
+    if sid == session.session_id {
+        cookies.remove_private(Cookie::named("session"));
+    }
+
+    Ok(())
+}
+
+/// Logs out everywhere else: revokes every session of the user except the current one.
+#[delete("/sessions")]
+pub(crate) async fn revoke_other_sessions(
+    session: SessionLoggedIn,
+    conn: DbConn,
+) -> Result<(), ErrorResponse> {
+    conn.run(move |c| {
+        use schema::sessions::dsl::*;
+
+        diesel::delete(sessions.filter(user_id.eq(session.user.id).and(id.ne(session.session_id))))
+            .execute(c)
+    })
+    .await
+    .map_err(|_| {
+        ErrorResponse::new(Status { code: 500 }, "Couldn't revoke sessions".to_string())
+    })?;
+
+    Ok(())
+}
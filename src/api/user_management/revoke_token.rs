@@ -0,0 +1,35 @@
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+
+/// Like minting, revoking needs a browser session, so a leaked token can't revoke the others.
+#[delete("/tokens/<tid>")]
+pub(crate) async fn revoke_token(
+    session: SessionLoggedIn,
+    tid: i32,
+    conn: DbConn,
+) -> Result<(), ErrorResponse> {
+    let deleted = conn
+        .run(move |c| {
+            use schema::api_tokens::dsl::*;
+
+            diesel::delete(api_tokens.filter(id.eq(tid).and(user_id.eq(session.user.id))))
+                .execute(c)
+        })
+        .await
+        .map_err(|_| {
+            ErrorResponse::new(Status { code: 500 }, "Couldn't revoke token".to_string())
+        })?;
+
+    if deleted == 0 {
+        return Err(ErrorResponse::new(
+            Status { code: 404 },
+            "Couldn't find token".to_string(),
+        ));
+    }
+
+    Ok(())
+}
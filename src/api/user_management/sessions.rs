@@ -1,34 +1,122 @@
-use std::collections::HashMap;
-use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use crate::api::user_management::login::SessionCookie;
 use crate::api::user_management::models::User;
 use crate::db::DbConn;
-use crate::error::ApiError;
+use crate::error::{ApiError, ErrorResponse};
 use crate::schema;
+use crate::schema::sessions;
+use diesel::dsl::{now, IntervalDsl};
 use diesel::prelude::*;
-use rocket::http::Status;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use rocket::http::{Cookie, CookieJar, Status};
 use rocket::outcome::{try_outcome, IntoOutcome};
 use rocket::request::{self, FromRequest, Outcome};
-use rocket::{Request, State};
+use rocket::Request;
+use serde::{Deserialize, Serialize};
 
-use super::models::{UserLoggedIn, UserOut};
+use super::models::{SessionLoggedIn, TokenLoggedIn, UserLoggedIn, UserOut};
 
-pub(crate) struct UserSession {
-    pub(crate) sessions: Mutex<HashMap<String, String>>,
+/// Sessions older than this are rejected, no matter how recently they were used.
+pub(crate) const SESSION_MAX_AGE_DAYS: i32 = 30;
+
+#[derive(Insertable)]
+#[table_name = "sessions"]
+struct NewSession {
+    session_key: String,
+    user_id: i32,
+    user_agent: Option<String>,
 }
 
-impl UserSession {
-    pub(crate) fn new() -> UserSession {
-        UserSession {
-            sessions: Mutex::new(HashMap::<String, String>::new()),
-        }
+#[derive(Serialize, Deserialize)]
+pub(super) struct SessionCookie {
+    pub(super) session_key: String,
+    pub(super) creation_time: SystemTime,
+}
+
+/// The `User-Agent` header of a request, if one was sent.
+pub(crate) struct UserAgent(pub(crate) Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAgent {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(UserAgent(
+            req.headers().get_one("User-Agent").map(str::to_string),
+        ))
     }
 }
 
+/// Stores a new session for `uid` and hands its key to the client as the private `session` cookie.
+pub(crate) async fn start_session(
+    conn: &DbConn,
+    cookies: &CookieJar<'_>,
+    uid: i32,
+    user_agent: UserAgent,
+) -> Result<(), ErrorResponse> {
+    prune_expired_sessions(conn).await;
+
+    let key = generate_session_key();
+    let new_session = NewSession {
+        session_key: key.clone(),
+        user_id: uid,
+        user_agent: user_agent.0,
+    };
+
+    conn.run(move |c| {
+        diesel::insert_into(schema::sessions::table)
+            .values(&new_session)
+            .execute(c)
+    })
+    .await
+    .map_err(|_| {
+        ErrorResponse::new(
+            Status { code: 500 },
+            "Couldn't update user session".to_string(),
+        )
+    })?;
+
+    let cookie = SessionCookie {
+        session_key: key,
+        creation_time: SystemTime::now(),
+    };
+
+    let cookie_string = serde_json::to_string(&cookie).map_err(|err| {
+        ErrorResponse::new(
+            Status { code: 500 },
+            format!("Couldn't create session cookie {}", err),
+        )
+    })?;
+
+    cookies.add_private(Cookie::new("session", cookie_string));
+
+    Ok(())
+}
+
+/// Drops every session that has outlived [`SESSION_MAX_AGE_DAYS`], for all users.
+async fn prune_expired_sessions(conn: &DbConn) {
+    conn.run(|c| {
+        use schema::sessions::dsl::*;
+
+        diesel::delete(sessions.filter(created_at.le(now - SESSION_MAX_AGE_DAYS.days()))).execute(c)
+    })
+    .await
+    .ok();
+}
+
+fn generate_session_key() -> String {
+    const LEN: usize = 32;
+
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(LEN)
+        .map(char::from)
+        .collect()
+}
+
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for UserLoggedIn {
+impl<'r> FromRequest<'r> for SessionLoggedIn {
     type Error = ApiError;
 
     async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
@@ -51,7 +139,7 @@ impl<'r> FromRequest<'r> for UserLoggedIn {
             .map_err(|_| ApiError::new("Couldn't determine session age".to_string()))
             .or_forward(()));
 
-        let max_age = Duration::from_secs(60 * 60 * 24 * 30);
+        let max_age = Duration::from_secs(60 * 60 * 24 * SESSION_MAX_AGE_DAYS as u64);
         if session_age > max_age {
             return request::Outcome::Failure((
                 Status { code: 401 },
@@ -59,26 +147,6 @@ impl<'r> FromRequest<'r> for UserLoggedIn {
             ));
         }
 
-        let sessions = try_outcome!(req.guard::<&State<UserSession>>().await.map_failure(|_| {
-            (
-                Status { code: 500 },
-                ApiError::new("Couldn't get UserSession".to_string()),
-            )
-        }));
-        let user_id = {
-            let sessions = try_outcome!(sessions
-                .sessions
-                .lock()
-                .map_err(|_| ApiError::new("Couldn't get user sessions".to_string()))
-                .or_forward(()));
-
-            try_outcome!(sessions
-                .get(&session_cookie_value.session_key)
-                .ok_or_else(|| ApiError::new("No session found".to_string()))
-                .or_forward(()))
-            .clone()
-        };
-
         let conn = try_outcome!(req.guard::<DbConn>().await.map_failure(|_| {
             (
                 Status { code: 500 },
@@ -86,27 +154,68 @@ impl<'r> FromRequest<'r> for UserLoggedIn {
             )
         }));
 
-        use schema::users::dsl::*;
+        let key = session_cookie_value.session_key;
+        let (sid, session_user) = try_outcome!(
+            conn.run(move |c| {
+                use schema::sessions::dsl::*;
+
+                // The server-side creation time is authoritative, the cookie can't extend it
+                diesel::update(
+                    sessions
+                        .filter(session_key.eq(key))
+                        .filter(created_at.gt(now - SESSION_MAX_AGE_DAYS.days())),
+                )
+                .set(last_seen.eq(now))
+                .returning((id, user_id))
+                .get_result::<(i32, i32)>(c)
+                .optional()
+                .map_err(|_| ApiError::new("Couldn't load session from database".to_string()))
+                .and_then(|session_user| {
+                    session_user.ok_or_else(|| ApiError::new("No session found".to_string()))
+                })
+                .or_forward(())
+            })
+            .await
+        );
 
-        let user_vec = try_outcome!(
-            conn.run(|c| users
-                .filter(sub.eq(user_id))
-                .load::<User>(c)
-                .map_err(|_| ApiError::new("Couldn't load user from database".to_string()))
-                .or_forward(()))
-                .await
+        let user = try_outcome!(
+            conn.run(move |c| {
+                use schema::users::dsl::*;
+
+                users
+                    .find(session_user)
+                    .first::<User>(c)
+                    .map_err(|_| ApiError::new("User not in database".to_string()))
+                    .or_forward(())
+            })
+            .await
         );
 
-        let user = try_outcome!(user_vec
-            .first()
-            .ok_or_else(|| { ApiError::new("User not in database".to_string()) })
-            .or_forward(()));
+        Outcome::Success(SessionLoggedIn {
+            session_id: sid,
+            user: UserOut {
+                id: user.id,
+                sub: user.sub,
+                username: user.username,
+                email: user.email,
+            },
+        })
+    }
+}
 
-        Outcome::Success(UserLoggedIn(UserOut {
-            id: user.id,
-            sub: user.sub.clone(),
-            username: user.username.clone(),
-            email: user.email.clone(),
-        }))
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserLoggedIn {
+    type Error = ApiError;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        // Browsers authenticate with the session cookie, scripts with a bearer token
+        match req.guard::<SessionLoggedIn>().await {
+            Outcome::Success(session) => Outcome::Success(UserLoggedIn(session.user)),
+            Outcome::Failure(failure) => Outcome::Failure(failure),
+            Outcome::Forward(_) => req
+                .guard::<TokenLoggedIn>()
+                .await
+                .map(|token| UserLoggedIn(token.0)),
+        }
     }
 }
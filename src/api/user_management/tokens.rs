@@ -0,0 +1,118 @@
+use crate::api::user_management::models::{TokenLoggedIn, User, UserOut};
+use crate::db::DbConn;
+use crate::error::ApiError;
+use crate::schema;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use rocket::http::{Method, Status};
+use rocket::outcome::{try_outcome, IntoOutcome};
+use rocket::request::{self, FromRequest, Outcome};
+use rocket::Request;
+use sha2::{Digest, Sha256};
+
+/// Allows requests that only read data (`GET` and `HEAD`).
+pub(crate) const SCOPE_READ: &str = "read";
+/// Allows every request that changes data.
+pub(crate) const SCOPE_WRITE: &str = "write";
+pub(crate) const SCOPES: [&str; 2] = [SCOPE_READ, SCOPE_WRITE];
+
+const TOKEN_PREFIX: &str = "tw_";
+
+pub(super) fn generate_token() -> String {
+    const LEN: usize = 40;
+
+    let secret: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(LEN)
+        .map(char::from)
+        .collect();
+
+    format!("{}{}", TOKEN_PREFIX, secret)
+}
+
+/// Tokens are random and long, so a plain SHA-256 is enough to keep them out of the database.
+pub(super) fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn required_scope(method: Method) -> &'static str {
+    match method {
+        Method::Get | Method::Head => SCOPE_READ,
+        _ => SCOPE_WRITE,
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for TokenLoggedIn {
+    type Error = ApiError;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let token = try_outcome!(req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .map(|token| hash_token(token.trim()))
+            .ok_or_else(|| ApiError::new("No bearer token set".to_string()))
+            .or_forward(()));
+
+        let conn = try_outcome!(req.guard::<DbConn>().await.map_failure(|_| {
+            (
+                Status { code: 500 },
+                ApiError::new("Couldn't get database connection".to_string()),
+            )
+        }));
+
+        let (token_user, token_scopes) = try_outcome!(conn
+            .run(move |c| {
+                use schema::api_tokens::dsl::*;
+
+                diesel::update(
+                    api_tokens
+                        .filter(token_hash.eq(token))
+                        .filter(expires_at.is_null().or(expires_at.gt(now.nullable()))),
+                )
+                .set(last_used.eq(now.nullable()))
+                .returning((user_id, scopes))
+                .get_result::<(i32, Vec<String>)>(c)
+                .optional()
+            })
+            .await
+            .map_err(|_| ApiError::new("Couldn't load token from database".to_string()))
+            .and_then(|token| token.ok_or_else(|| ApiError::new("Invalid token".to_string())))
+            .into_outcome(Status { code: 401 }));
+
+        // A token without scopes may do everything its owner may
+        let scope = required_scope(req.method());
+        if !token_scopes.is_empty() && !token_scopes.iter().any(|s| s == scope) {
+            return Outcome::Failure((
+                Status { code: 403 },
+                ApiError::new(format!("Token lacks the {} scope", scope)),
+            ));
+        }
+
+        let user = try_outcome!(
+            conn.run(move |c| {
+                use schema::users::dsl::*;
+
+                users
+                    .find(token_user)
+                    .first::<User>(c)
+                    .map_err(|_| ApiError::new("User not in database".to_string()))
+                    .into_outcome(Status { code: 401 })
+            })
+            .await
+        );
+
+        Outcome::Success(TokenLoggedIn(UserOut {
+            id: user.id,
+            sub: user.sub,
+            username: user.username,
+            email: user.email,
+        }))
+    }
+}
@@ -0,0 +1,73 @@
+use crate::api::user_management::local_accounts::LOCAL_PROVIDER;
+use crate::api::user_management::models::SessionLoggedIn;
+use crate::db::DbConn;
+use crate::error::ErrorResponse;
+use crate::schema;
+use diesel::prelude::*;
+use rocket::http::Status;
+
+/// Unlinks an identity, refusing to remove the last one as the account couldn't be signed into.
+#[delete("/identities/<iid>")]
+pub(crate) async fn unlink_identity(
+    iid: i32,
+    session: SessionLoggedIn,
+    conn: DbConn,
+) -> Result<(), ErrorResponse> {
+    let uid = session.user.id;
+
+    conn.run(move |c| {
+        c.build_transaction()
+            .read_write()
+            .run::<_, ErrorResponse, _>(|| {
+                use schema::user_identities::dsl::*;
+                use schema::users;
+
+                // Lock the user's identities so two concurrent unlinks can't remove the last two
+                let identities = user_identities
+                    .filter(user_id.eq(uid))
+                    .order(created_at.asc())
+                    .select((id, provider, subject))
+                    .for_update()
+                    .load::<(i32, String, String)>(c)?;
+
+                let (_, removed_provider, removed_subject) = identities
+                    .iter()
+                    .find(|(identity_id, _, _)| *identity_id == iid)
+                    .ok_or_else(|| {
+                        ErrorResponse::new(
+                            Status { code: 404 },
+                            "Couldn't find identity".to_string(),
+                        )
+                    })?;
+                let (_, next_provider, next_subject) = identities
+                    .iter()
+                    .find(|(identity_id, _, _)| *identity_id != iid)
+                    .ok_or_else(|| {
+                        ErrorResponse::new(
+                            Status { code: 409 },
+                            "Can't unlink the last identity of an account".to_string(),
+                        )
+                    })?;
+
+                diesel::delete(user_identities.filter(id.eq(iid))).execute(c)?;
+
+                // users.sub always names one of the user's own identities
+                diesel::update(
+                    users::table
+                        .find(uid)
+                        .filter(users::sub.eq(format!("{}:{}", removed_provider, removed_subject))),
+                )
+                .set(users::sub.eq(format!("{}:{}", next_provider, next_subject)))
+                .execute(c)?;
+
+                if removed_provider == LOCAL_PROVIDER {
+                    diesel::update(users::table.find(uid))
+                        .set(users::password_hash.eq(None::<String>))
+                        .execute(c)?;
+                }
+
+                Ok(())
+            })
+    })
+    .await
+}
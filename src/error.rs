@@ -31,6 +31,17 @@ impl ErrorResponse<ApiError> {
             status,
         }
     }
+
+    pub(crate) fn message(&self) -> &str {
+        &self.json.err
+    }
+}
+
+/// Lets transactions that can fail with an API error use `?` on database errors.
+impl From<diesel::result::Error> for ErrorResponse<ApiError> {
+    fn from(err: diesel::result::Error) -> Self {
+        ErrorResponse::new(Status { code: 500 }, format!("Database error: {}", err))
+    }
 }
 
 impl<'r, T: serde::Serialize> Responder<'r, 'r> for ErrorResponse<T> {
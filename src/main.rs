@@ -12,12 +12,28 @@ extern crate dotenv;
 #[macro_use]
 extern crate diesel_migrations;
 
+use crate::api::analytics::{coverage, items, monthly, smart_tags, streaks, tags, weekdays};
+use crate::api::data_management::{export, import};
 use crate::api::item_management::{
-    add_tag, add_use, create, create_tag, delete, delete_tag, edit, get_item, get_item_tags,
-    get_tags, list, modify_inventory, remove_tag,
+    add_tag, add_use, bulk_tags, calendar, create, create_tag, delete, delete_tag, delete_use,
+    edit, edit_tag, edit_use, get_inventory, get_item, get_item_tags, get_tags, get_uses, list,
+    merge_items, merge_tags, modify_inventory, move_tag, remove_tag,
 };
-use crate::api::user_management::login;
-use api::user_management::sessions::UserSession;
+use crate::api::outfit_management::{
+    add_item, create as create_outfit, delete as delete_outfit, edit as edit_outfit, get_outfit,
+    list as list_outfits, remove_item, wear,
+};
+use crate::api::reports::{cost_per_wear, neglected, outflow};
+use crate::api::smart_tag_management::{
+    create as create_smart_tag, delete as delete_smart_tag, edit as edit_smart_tag,
+    list as list_smart_tags,
+};
+use crate::api::suggestions::suggest;
+use crate::api::user_management::{
+    create_token, delete_account, link_identity, list_identities, list_sessions, list_tokens,
+    local_accounts, login, logout, revoke_session, revoke_token, unlink_identity,
+};
+use api::user_management::providers::IdentityProviders;
 use db::{run_db_migrations, DbConn};
 use rocket::fairing::AdHoc;
 use settings::Settings;
@@ -36,7 +52,11 @@ async fn rocket() -> _ {
     rocket::build()
         .attach(DbConn::fairing())
         .attach(AdHoc::on_ignite("Run Migrations", run_db_migrations))
-        .manage(UserSession::new())
+        .attach(AdHoc::on_ignite(
+            "Create Initial Account",
+            local_accounts::create_initial_account,
+        ))
+        .manage(IdentityProviders::new(&settings))
         .manage(settings)
         .mount("/", routes![index])
         .mount(
@@ -44,8 +64,24 @@ async fn rocket() -> _ {
             routes![
                 index,
                 login::login,
+                login::login_providers,
                 login::check_login,
                 login::check_login_unauthorised,
+                local_accounts::register,
+                local_accounts::local_login,
+                local_accounts::link_local,
+                local_accounts::change_password,
+                list_identities::list_identities,
+                link_identity::link_identity,
+                unlink_identity::unlink_identity,
+                delete_account::delete_account,
+                logout::logout,
+                list_sessions::list_sessions,
+                revoke_session::revoke_session,
+                revoke_session::revoke_other_sessions,
+                create_token::create_token,
+                list_tokens::list_tokens,
+                revoke_token::revoke_token,
                 create::create_item,
                 edit::edit_item,
                 list::get_items,
@@ -53,13 +89,48 @@ async fn rocket() -> _ {
                 delete::delete_item,
                 get_item::get_item_image,
                 add_use::add_use,
+                get_uses::get_uses,
+                edit_use::edit_use,
+                delete_use::delete_use,
+                calendar::get_calendar,
+                get_inventory::get_inventory,
+                merge_items::merge_items,
+                merge_tags::merge_tags,
                 modify_inventory::modify_inventory,
                 create_tag::create_tag,
                 delete_tag::delete_tag,
+                edit_tag::edit_tag,
+                move_tag::move_tag,
                 add_tag::add_tag,
+                bulk_tags::bulk_tags,
                 remove_tag::remove_tag,
                 get_item_tags::get_item_tags,
                 get_tags::get_tags,
+                create_outfit::create_outfit,
+                list_outfits::get_outfits,
+                get_outfit::get_outfit,
+                edit_outfit::edit_outfit,
+                add_item::add_item,
+                remove_item::remove_item,
+                delete_outfit::delete_outfit,
+                wear::wear_outfit,
+                create_smart_tag::create_smart_tag,
+                list_smart_tags::get_smart_tags,
+                edit_smart_tag::edit_smart_tag,
+                delete_smart_tag::delete_smart_tag,
+                export::export,
+                import::import,
+                cost_per_wear::cost_per_wear_report,
+                neglected::neglected_items,
+                outflow::outflow_report,
+                suggest::suggestions,
+                items::item_ranking,
+                monthly::monthly_wears,
+                tags::tag_wears,
+                smart_tags::smart_tag_wears,
+                weekdays::weekday_wears,
+                streaks::streaks,
+                coverage::coverage,
             ],
         )
 }
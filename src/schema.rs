@@ -1,9 +1,25 @@
+table! {
+    api_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        token_name -> Varchar,
+        token_hash -> Varchar,
+        scopes -> Array<Text>,
+        created_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+        last_used -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     item_inventory (id) {
         id -> Int4,
         movement -> Int4,
         item_id -> Int4,
         update_time -> Timestamp,
+        reason -> Varchar,
+        note -> Nullable<Text>,
+        value_cents -> Nullable<Int4>,
     }
 }
 
@@ -20,6 +36,58 @@ table! {
         id -> Int4,
         user_id -> Int4,
         item_name -> Varchar,
+        brand -> Nullable<Varchar>,
+        size -> Nullable<Varchar>,
+        color -> Nullable<Varchar>,
+        material -> Nullable<Varchar>,
+        purchase_date -> Nullable<Date>,
+        purchase_price_cents -> Nullable<Int4>,
+        purchase_currency -> Nullable<Varchar>,
+        notes -> Nullable<Text>,
+    }
+}
+
+table! {
+    outfit_items (id) {
+        id -> Int4,
+        outfit_id -> Int4,
+        item_id -> Int4,
+    }
+}
+
+table! {
+    outfit_wears (id) {
+        id -> Int4,
+        outfit_id -> Int4,
+        date -> Date,
+    }
+}
+
+table! {
+    outfits (id) {
+        id -> Int4,
+        user_id -> Int4,
+        outfit_name -> Varchar,
+    }
+}
+
+table! {
+    sessions (id) {
+        id -> Int4,
+        session_key -> Varchar,
+        user_id -> Int4,
+        created_at -> Timestamp,
+        last_seen -> Timestamp,
+        user_agent -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    smart_tags (id) {
+        id -> Int4,
+        user_id -> Int4,
+        smart_tag_name -> Varchar,
+        expression -> Text,
     }
 }
 
@@ -28,6 +96,19 @@ table! {
         id -> Int4,
         user_id -> Int4,
         tag_name -> Varchar,
+        color -> Nullable<Varchar>,
+        icon -> Nullable<Varchar>,
+        parent_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    user_identities (id) {
+        id -> Int4,
+        user_id -> Int4,
+        provider -> Varchar,
+        subject -> Varchar,
+        created_at -> Timestamp,
     }
 }
 
@@ -37,6 +118,7 @@ table! {
         sub -> Varchar,
         username -> Varchar,
         email -> Varchar,
+        password_hash -> Nullable<Varchar>,
     }
 }
 
@@ -45,17 +127,26 @@ table! {
         id -> Int4,
         item_id -> Int4,
         date -> Date,
+        outfit_wear_id -> Nullable<Int4>,
     }
 }
 
 joinable!(item_tags -> tags (tag_id));
 joinable!(uses -> items (item_id));
+joinable!(uses -> outfit_wears (outfit_wear_id));
 
 allow_tables_to_appear_in_same_query!(
+    api_tokens,
     item_inventory,
     item_tags,
     items,
+    outfit_items,
+    outfit_wears,
+    outfits,
+    sessions,
+    smart_tags,
     tags,
+    user_identities,
     users,
     uses,
 );
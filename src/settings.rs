@@ -4,12 +4,30 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize)]
 pub struct Settings {
     pub image_folder: String,
-    pub google_client_id: String,
+    pub google_client_id: Option<String>,
+    pub oidc_provider_name: Option<String>,
+    pub oidc_issuer: Option<String>,
+    pub oidc_audience: Option<String>,
+    /// Discovered from the issuer's `.well-known/openid-configuration` when not set.
+    pub oidc_jwks_url: Option<String>,
+    /// Allows signing in with a username and password stored on this server.
+    pub local_accounts: bool,
+    /// Lets anyone create a local account, only relevant with `local_accounts`.
+    pub registration_enabled: bool,
+    /// Local account created on startup unless it exists, the way in when registration is disabled.
+    pub initial_account_username: Option<String>,
+    pub initial_account_password: Option<String>,
+    /// Enables the offline `dev` identity provider, refused outside the development profile.
+    pub dev_auth_secret: Option<String>,
 }
 
 impl Settings {
     pub fn new() -> Self {
         Config::builder()
+            .set_default("local_accounts", false)
+            .unwrap()
+            .set_default("registration_enabled", true)
+            .unwrap()
             .add_source(Environment::default())
             .build()
             .unwrap()